@@ -4,7 +4,10 @@ use base64::{Engine, prelude::BASE64_URL_SAFE_NO_PAD};
 use jsonwebtoken::{Validation, crypto, decode};
 use serde::Serialize;
 
-use crate::{error::Error, something_went_wrong, unauthorized};
+use crate::{
+    error::{Error, code::CoreErrorCode},
+    something_went_wrong, unauthorized,
+};
 
 use super::{auth_options::AuthOptions, jwt_claims::JwtClaims};
 
@@ -55,11 +58,14 @@ impl AuthService {
         match decode::<JwtClaims<T>>(token, &self.auth_options.decoding_key, &validation) {
             Ok(x) => {
                 if x.claims.purpose != purpose.to_string() {
-                    return Err(unauthorized!("Token purpose does not match."));
+                    return Err(unauthorized!(
+                        code = CoreErrorCode::TokenPurposeMismatch,
+                        "Token purpose does not match."
+                    ));
                 }
-                return Ok(x.claims);
+                Ok(x.claims)
             }
-            Err(e) => Err(unauthorized!("Error while decoding token : {e}")),
+            Err(e) => Err(e.into()),
         }
     }
 
@@ -92,7 +98,7 @@ fn generate_encoded_claims(
         ]
         .join(",");
     }
-    return Ok(json);
+    Ok(json)
 }
 
 fn b64_encode_part<T: Serialize>(input: &T) -> Result<String, Error> {
@@ -3,17 +3,20 @@ use std::sync::Arc;
 use axum::{
     Router,
     body::Body,
+    extract::FromRequestParts,
     http::Request,
-    middleware::{self, Next},
-    response::Response,
+    middleware::Next,
+    response::{IntoResponse, Response},
 };
 use axum_extra::{
     TypedHeader,
     headers::{Authorization, authorization::Bearer},
 };
-use reqwest::StatusCode;
 
-use crate::web_core::WebCoreState;
+use crate::{
+    error::Error, metrics::metrics, middleware::middleware_handler::crate_middleware_handler,
+    web_core::WebCoreState,
+};
 
 use super::{
     auth_service::{AuthService, TokenPurpose},
@@ -26,18 +29,43 @@ pub async fn authentication_middleware(
     mut req: Request<Body>,
     next: Next,
     auth_service: Arc<AuthService>,
-) -> Result<Response, StatusCode> {
+) -> Result<Response, Error> {
     let auth_service = auth_service.clone();
     let claims: JwtClaims<()> = auth_service
-        .decode_token::<_>(&bearer.token().to_string(), TokenPurpose::Access)
-        .map_err(|_| StatusCode::UNAUTHORIZED)?;
+        .decode_token::<_>(bearer.token(), TokenPurpose::Access)
+        .inspect_err(|e| metrics().record_auth_failure(e.code()))?;
 
-    req.extensions_mut()
-        .insert(claims.additional_claims.clone());
+    req.extensions_mut().insert(claims.additional_claims);
     let authenticated_user: AuthenticatedUser = claims.into();
-    req.extensions_mut().insert(authenticated_user);
+    req.extensions_mut().insert(authenticated_user.clone());
+
+    // Also added to the response, so outer layers (eg: error reporting) know the subject.
+    let mut response = next.run(req).await;
+    response.extensions_mut().insert(authenticated_user);
+    Ok(response)
+}
 
-    Ok(next.run(req).await)
+///Middleware of `with_auth_layer`, also usable with `Router::route_layer` through
+///`crate_middleware_handler`.
+pub async fn authenticate(
+    req: Request<Body>,
+    next: Next,
+    auth_service: Arc<AuthService>,
+) -> Response {
+    let (mut parts, body) = req.into_parts();
+    let typed_header =
+        TypedHeader::<Authorization<Bearer>>::from_request_parts(&mut parts, &()).await;
+    let req = Request::from_parts(parts, body);
+    match typed_header {
+        Ok(typed_header) => authentication_middleware(typed_header, req, next, auth_service)
+            .await
+            .into_response(),
+        Err(rejection) => {
+            let error = Error::from(rejection);
+            metrics().record_auth_failure(error.code());
+            error.into_response()
+        }
+    }
 }
 
 pub trait AuthMiddlewareLayer {
@@ -49,9 +77,8 @@ where
     T: Clone + Send + Sync + 'static,
 {
     fn with_auth_layer(self, auth_service: Arc<AuthService>) -> Self {
-        self.layer(middleware::from_fn(move |typed_header: TypedHeader<Authorization<Bearer>>, req: Request<Body>, next: Next| {
-            let auth_service = auth_service.clone();
-            async move { authentication_middleware(typed_header, req, next, auth_service).await }
+        self.layer(crate_middleware_handler(move |req, next| {
+            authenticate(req, next, auth_service.clone())
         }))
     }
 }
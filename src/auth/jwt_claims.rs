@@ -11,7 +11,7 @@ use serde::{Serialize, de::DeserializeOwned};
 use std::time::Duration;
 use uuid::Uuid;
 
-use crate::{error::Error, something_went_wrong, web_core::WebCoreState};
+use crate::{error::Error, web_core::WebCoreState};
 
 use super::{auth_service::TokenPurpose, authenticated_user::AuthenticatedUser};
 
@@ -62,29 +62,20 @@ where
     WebCoreState<()>: FromRef<S>,
 {
     type Rejection = Error;
-    fn from_request_parts(
-        parts: &mut Parts,
-        state: &S,
-    ) -> impl Future<Output = Result<Self, Self::Rejection>> + Send {
-        async move {
-            let TypedHeader(Authorization(bearer)) =
-                TypedHeader::<Authorization<Bearer>>::from_request_parts(parts, state)
-                    .await
-                    .map_err(|e| {
-                        something_went_wrong!("Error while getting bearer token : {:?}", e)
-                    })?;
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let TypedHeader(Authorization(bearer)) =
+            TypedHeader::<Authorization<Bearer>>::from_request_parts(parts, state).await?;
 
-            let state = WebCoreState::<()>::from_ref(state);
+        let state = WebCoreState::<()>::from_ref(state);
 
-            state
-                .auth_service
-                .decode_token(bearer.token(), TokenPurpose::Access)
-        }
+        state
+            .auth_service
+            .decode_token(bearer.token(), TokenPurpose::Access)
     }
 }
 
-impl<T> Into<AuthenticatedUser> for JwtClaims<T> {
-    fn into(self) -> AuthenticatedUser {
-        AuthenticatedUser::new(self.sub.clone())
+impl<T> From<JwtClaims<T>> for AuthenticatedUser {
+    fn from(val: JwtClaims<T>) -> Self {
+        AuthenticatedUser::new(val.sub.clone())
     }
 }
@@ -4,4 +4,5 @@ pub mod authenticated_user;
 pub mod authentication_middleware;
 pub mod jwt_claims;
 pub mod password_hasher;
+pub mod roles;
 pub mod google;
\ No newline at end of file
@@ -0,0 +1,95 @@
+use std::sync::Arc;
+
+use axum::{
+    Router,
+    body::Body,
+    extract::FromRequestParts,
+    http::Request,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use axum_extra::{
+    TypedHeader,
+    headers::{Authorization, authorization::Bearer},
+};
+use serde_json::{Map, Value};
+
+use crate::{
+    error::{Error, code::CoreErrorCode},
+    forbidden,
+    middleware::middleware_handler::crate_middleware_handler,
+    web_core::WebCoreState,
+};
+
+use super::{
+    auth_service::{AuthService, TokenPurpose},
+    jwt_claims::JwtClaims,
+};
+
+///Claim holding the roles of the subject, either a string or a list of strings.
+pub const ROLES_CLAIM: &str = "roles";
+
+fn has_role(claims: &Map<String, Value>, role: &str) -> bool {
+    match claims.get(ROLES_CLAIM) {
+        Some(Value::String(x)) => x == role,
+        Some(Value::Array(roles)) => roles.iter().any(|x| x.as_str() == Some(role)),
+        _ => false,
+    }
+}
+
+///Answers 403 when the access token does not have `role` in its `roles` claim.
+pub async fn role_middleware(
+    TypedHeader(Authorization(bearer)): TypedHeader<Authorization<Bearer>>,
+    req: Request<Body>,
+    next: Next,
+    auth_service: Arc<AuthService>,
+    role: Arc<str>,
+) -> Result<Response, Error> {
+    let claims: JwtClaims<Map<String, Value>> =
+        auth_service.decode_token(bearer.token(), TokenPurpose::Access)?;
+    let claims = claims.additional_claims.unwrap_or_default();
+    if !has_role(&claims, &role) {
+        return Err(forbidden!(
+            code = CoreErrorCode::MissingRole,
+            "Requires the {role} role"
+        ));
+    }
+    Ok(next.run(req).await)
+}
+
+///Middleware of `with_role_layer`, also usable with `Router::route_layer` through
+///`crate_middleware_handler`.
+pub async fn require_role(
+    req: Request<Body>,
+    next: Next,
+    auth_service: Arc<AuthService>,
+    role: Arc<str>,
+) -> Response {
+    let (mut parts, body) = req.into_parts();
+    let typed_header =
+        TypedHeader::<Authorization<Bearer>>::from_request_parts(&mut parts, &()).await;
+    let req = Request::from_parts(parts, body);
+    match typed_header {
+        Ok(typed_header) => role_middleware(typed_header, req, next, auth_service, role)
+            .await
+            .into_response(),
+        Err(rejection) => Error::from(rejection).into_response(),
+    }
+}
+
+pub trait RoleLayer {
+    ///Add it before `with_auth_layer`, so that unauthenticated requests get 401 rather than 403.
+    fn with_role_layer(self, auth_service: Arc<AuthService>, role: &str) -> Self;
+}
+
+impl<T> RoleLayer for Router<WebCoreState<T>>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    fn with_role_layer(self, auth_service: Arc<AuthService>, role: &str) -> Self {
+        let role: Arc<str> = Arc::from(role);
+        self.layer(crate_middleware_handler(move |req, next| {
+            require_role(req, next, auth_service.clone(), role.clone())
+        }))
+    }
+}
@@ -0,0 +1,517 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use crate::{
+    auth::auth_options::AuthOptions,
+    middleware::{
+        logging_middleware::LogFormat, redaction::REDACTED, request_limits::RequestLimitsOptions,
+    },
+    web_core::{WebCoreOptions, WebCoreState},
+};
+
+const JWT_SECRET: &str = "jwt_secret";
+const ACCESS_TOKEN_LIFETIME_SECS: &str = "access_token_lifetime_secs";
+const REFRESH_TOKEN_LIFETIME_SECS: &str = "refresh_token_lifetime_secs";
+const AUDIENCE: &str = "audience";
+const FRONTEND_URLS: &str = "frontend_urls";
+const DATABASE_URL: &str = "database_url";
+const DATABASE_POOL_SIZE: &str = "database_pool_size";
+const LOG_FORMAT: &str = "log_format";
+const REQUEST_TIMEOUT_SECS: &str = "request_timeout_secs";
+const MAX_BODY_BYTES: &str = "max_body_bytes";
+
+const KEYS: [&str; 10] = [
+    JWT_SECRET,
+    ACCESS_TOKEN_LIFETIME_SECS,
+    REFRESH_TOKEN_LIFETIME_SECS,
+    AUDIENCE,
+    FRONTEND_URLS,
+    DATABASE_URL,
+    DATABASE_POOL_SIZE,
+    LOG_FORMAT,
+    REQUEST_TIMEOUT_SECS,
+    MAX_BODY_BYTES,
+];
+
+const MIN_JWT_SECRET_BYTES: usize = 32;
+
+///Where a configuration value came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigSource {
+    Default,
+    File(PathBuf),
+    DotEnv { path: PathBuf, variable: String },
+    Env(String),
+}
+
+impl std::fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigSource::Default => f.write_str("default value"),
+            ConfigSource::File(path) => write!(f, "config file `{}`", path.display()),
+            ConfigSource::DotEnv { path, variable } => {
+                write!(f, "`{variable}` in `{}`", path.display())
+            }
+            ConfigSource::Env(variable) => write!(f, "environment variable `{variable}`"),
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigError {
+    #[error("Could not read {source_name} : {source}")]
+    Read {
+        source_name: ConfigSource,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("Malformed {source_name} : {message}")]
+    Malformed {
+        source_name: ConfigSource,
+        message: String,
+    },
+    #[error("Unknown key `{key}` in {source_name}.")]
+    UnknownKey {
+        key: String,
+        source_name: ConfigSource,
+    },
+    #[error("Missing `{key}`, set it in the config file or with `{variable}`.")]
+    Missing { key: &'static str, variable: String },
+    #[error("Invalid `{key}` from {source_name} : {message}")]
+    Invalid {
+        key: &'static str,
+        source_name: ConfigSource,
+        message: String,
+    },
+}
+
+///A value which is never printed, eg: in `Debug` output or logs.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Secret(String);
+
+impl Secret {
+    pub fn new(value: impl Into<String>) -> Self {
+        Self(value.into())
+    }
+
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::fmt::Debug for Secret {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+enum RawValue {
+    Toml(toml::Value),
+    Text(String),
+}
+
+///Values of every layer, the last layer setting a key wins.
+struct Layers {
+    values: HashMap<&'static str, (RawValue, ConfigSource)>,
+    env_prefix: String,
+}
+
+impl Layers {
+    fn key(name: &str) -> Option<&'static str> {
+        KEYS.into_iter().find(|x| *x == name)
+    }
+
+    fn variable(&self, key: &str) -> String {
+        match self.env_prefix.is_empty() {
+            true => key.to_uppercase(),
+            false => format!("{}_{}", self.env_prefix, key.to_uppercase()),
+        }
+    }
+
+    ///Key of an environment variable, eg: `APP_JWT_SECRET` is `jwt_secret`.
+    fn key_of_variable(&self, variable: &str) -> Option<&'static str> {
+        let name = match self.env_prefix.is_empty() {
+            true => variable,
+            false => variable
+                .strip_prefix(self.env_prefix.as_str())?
+                .strip_prefix('_')?,
+        };
+        Self::key(&name.to_lowercase())
+    }
+
+    fn invalid(
+        key: &'static str,
+        source: &ConfigSource,
+        message: impl Into<String>,
+    ) -> ConfigError {
+        ConfigError::Invalid {
+            key,
+            source_name: source.clone(),
+            message: message.into(),
+        }
+    }
+
+    fn string(&self, key: &'static str) -> Result<Option<(String, ConfigSource)>, ConfigError> {
+        match self.values.get(key) {
+            None => Ok(None),
+            Some((RawValue::Text(value), source)) => Ok(Some((value.clone(), source.clone()))),
+            Some((RawValue::Toml(toml::Value::String(value)), source)) => {
+                Ok(Some((value.clone(), source.clone())))
+            }
+            Some((RawValue::Toml(_), source)) => {
+                Err(Self::invalid(key, source, "expected a string"))
+            }
+        }
+    }
+
+    fn required_string(&self, key: &'static str) -> Result<(String, ConfigSource), ConfigError> {
+        self.string(key)?.ok_or_else(|| ConfigError::Missing {
+            key,
+            variable: self.variable(key),
+        })
+    }
+
+    fn number(&self, key: &'static str, default: u64) -> Result<(u64, ConfigSource), ConfigError> {
+        match self.values.get(key) {
+            None => Ok((default, ConfigSource::Default)),
+            Some((RawValue::Text(value), source)) => match value.trim().parse() {
+                Ok(value) => Ok((value, source.clone())),
+                Err(_) => Err(Self::invalid(
+                    key,
+                    source,
+                    format!("`{value}` is not a number"),
+                )),
+            },
+            Some((RawValue::Toml(toml::Value::Integer(value)), source)) => {
+                match u64::try_from(*value) {
+                    Ok(value) => Ok((value, source.clone())),
+                    Err(_) => Err(Self::invalid(key, source, "expected a positive number")),
+                }
+            }
+            Some((RawValue::Toml(_), source)) => {
+                Err(Self::invalid(key, source, "expected a number"))
+            }
+        }
+    }
+
+    fn positive_number(&self, key: &'static str, default: u64) -> Result<u64, ConfigError> {
+        match self.number(key, default)? {
+            (0, source) => Err(Self::invalid(key, &source, "must be greater than 0")),
+            (value, _) => Ok(value),
+        }
+    }
+
+    ///A toml array, or a comma separated list in environment variables.
+    fn list(&self, key: &'static str) -> Result<Vec<(String, ConfigSource)>, ConfigError> {
+        match self.values.get(key) {
+            None => Ok(vec![]),
+            Some((RawValue::Text(value), source)) => Ok(value
+                .split(',')
+                .map(str::trim)
+                .filter(|x| !x.is_empty())
+                .map(|x| (x.to_string(), source.clone()))
+                .collect()),
+            Some((RawValue::Toml(toml::Value::Array(values)), source)) => values
+                .iter()
+                .map(|x| match x {
+                    toml::Value::String(x) => Ok((x.clone(), source.clone())),
+                    _ => Err(Self::invalid(key, source, "expected a list of strings")),
+                })
+                .collect(),
+            Some((RawValue::Toml(_), source)) => {
+                Err(Self::invalid(key, source, "expected a list of strings"))
+            }
+        }
+    }
+}
+
+///Loads `WebCoreConfig` from, in increasing priority: defaults, a TOML file, a `.env` file and
+///environment variables.
+///
+///Keys are the same everywhere, environment variables are upper cased and prefixed,
+///eg: `jwt_secret` in the file is `APP_JWT_SECRET` in the environment.
+#[derive(Debug, Clone)]
+pub struct ConfigLoader {
+    file: Option<PathBuf>,
+    dotenv: Option<PathBuf>,
+    env_prefix: String,
+}
+
+impl Default for ConfigLoader {
+    fn default() -> Self {
+        Self {
+            file: None,
+            dotenv: None,
+            env_prefix: String::from("APP"),
+        }
+    }
+}
+
+impl ConfigLoader {
+    ///The file must exist.
+    pub fn with_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.file = Some(path.into());
+        self
+    }
+
+    ///Read when it exists. Values are not added to the process environment.
+    pub fn with_dotenv(mut self, path: impl Into<PathBuf>) -> Self {
+        self.dotenv = Some(path.into());
+        self
+    }
+
+    ///Defaults to `APP`. An empty prefix reads `JWT_SECRET`, `DATABASE_URL`...
+    pub fn with_env_prefix(mut self, env_prefix: impl Into<String>) -> Self {
+        self.env_prefix = env_prefix.into().trim_end_matches('_').to_uppercase();
+        self
+    }
+
+    pub fn load(&self) -> Result<WebCoreConfig, ConfigError> {
+        let mut layers = Layers {
+            values: HashMap::new(),
+            env_prefix: self.env_prefix.clone(),
+        };
+        if let Some(path) = &self.file {
+            load_file(&mut layers, path)?;
+        }
+        if let Some(path) = &self.dotenv {
+            load_dotenv(&mut layers, path)?;
+        }
+        //Names which are not unicode can not be one of the keys, values are reported.
+        for (variable, value) in std::env::vars_os() {
+            let Some(variable) = variable.to_str() else {
+                continue;
+            };
+            let Some(key) = layers.key_of_variable(variable) else {
+                continue;
+            };
+            let source = ConfigSource::Env(variable.to_string());
+            let Ok(value) = value.into_string() else {
+                return Err(ConfigError::Malformed {
+                    source_name: source,
+                    message: String::from("The value is not valid unicode."),
+                });
+            };
+            layers.values.insert(key, (RawValue::Text(value), source));
+        }
+        WebCoreConfig::from_layers(&layers)
+    }
+}
+
+fn load_file(layers: &mut Layers, path: &Path) -> Result<(), ConfigError> {
+    let source = ConfigSource::File(path.to_path_buf());
+    let content = std::fs::read_to_string(path).map_err(|e| ConfigError::Read {
+        source_name: source.clone(),
+        source: e,
+    })?;
+    let table = content
+        .parse::<toml::Table>()
+        .map_err(|e| ConfigError::Malformed {
+            source_name: source.clone(),
+            message: e.to_string(),
+        })?;
+    for (name, value) in table {
+        let key = Layers::key(&name).ok_or_else(|| ConfigError::UnknownKey {
+            key: name.clone(),
+            source_name: source.clone(),
+        })?;
+        layers
+            .values
+            .insert(key, (RawValue::Toml(value), source.clone()));
+    }
+    Ok(())
+}
+
+fn load_dotenv(layers: &mut Layers, path: &Path) -> Result<(), ConfigError> {
+    if !path.exists() {
+        return Ok(());
+    }
+    let malformed = |e: dotenvy::Error| ConfigError::Malformed {
+        source_name: ConfigSource::File(path.to_path_buf()),
+        message: e.to_string(),
+    };
+    for item in dotenvy::from_path_iter(path).map_err(malformed)? {
+        let (variable, value) = item.map_err(malformed)?;
+        if let Some(key) = layers.key_of_variable(&variable) {
+            let source = ConfigSource::DotEnv {
+                path: path.to_path_buf(),
+                variable,
+            };
+            layers.values.insert(key, (RawValue::Text(value), source));
+        }
+    }
+    Ok(())
+}
+
+///Settings most apps wire by hand in `main`, validated once at startup.
+///
+///```ignore
+///let config = WebCoreConfig::loader().with_file("config.toml").with_dotenv(".env").load()?;
+///let web_core_state = WebCoreState::new(AuthService::new(config.auth_options()), app_state);
+///let app = routes.with_web_core(config.web_core_options(web_core_state));
+///```
+#[derive(Debug, Clone)]
+pub struct WebCoreConfig {
+    pub jwt_secret: Secret,
+    pub access_token_lifetime: Duration,
+    pub refresh_token_lifetime: Duration,
+    pub audience: Option<String>,
+    ///Allowed as CORS origins, with their `www.` counterparts.
+    pub frontend_urls: Vec<String>,
+    pub database_url: Option<Secret>,
+    pub database_pool_size: u32,
+    pub log_format: LogFormat,
+    pub request_timeout: Duration,
+    pub max_body_bytes: usize,
+    database_url_variable: String,
+}
+
+impl WebCoreConfig {
+    pub fn loader() -> ConfigLoader {
+        ConfigLoader::default()
+    }
+
+    fn from_layers(layers: &Layers) -> Result<Self, ConfigError> {
+        let (jwt_secret, source) = layers.required_string(JWT_SECRET)?;
+        if jwt_secret.len() < MIN_JWT_SECRET_BYTES {
+            return Err(Layers::invalid(
+                JWT_SECRET,
+                &source,
+                format!("must be at least {MIN_JWT_SECRET_BYTES} bytes long"),
+            ));
+        }
+
+        let frontend_urls = layers
+            .list(FRONTEND_URLS)?
+            .into_iter()
+            .map(|(url, source)| match url::Url::parse(&url) {
+                Ok(parsed) if matches!(parsed.scheme(), "http" | "https") => Ok(url),
+                _ => Err(Layers::invalid(
+                    FRONTEND_URLS,
+                    &source,
+                    format!("`{url}` is not an http or https url"),
+                )),
+            })
+            .collect::<Result<_, _>>()?;
+
+        let database_url = match layers.string(DATABASE_URL)? {
+            Some((url, source)) => {
+                if !url.starts_with("postgres://") && !url.starts_with("postgresql://") {
+                    return Err(Layers::invalid(
+                        DATABASE_URL,
+                        &source,
+                        "expected a `postgres://` url",
+                    ));
+                }
+                Some(Secret::new(url))
+            }
+            None => None,
+        };
+
+        let (database_pool_size, source) = layers.number(DATABASE_POOL_SIZE, 10)?;
+        let database_pool_size = u32::try_from(database_pool_size)
+            .ok()
+            .filter(|x| *x > 0)
+            .ok_or_else(|| {
+                Layers::invalid(
+                    DATABASE_POOL_SIZE,
+                    &source,
+                    "must be between 1 and 4294967295",
+                )
+            })?;
+
+        let log_format = match layers.string(LOG_FORMAT)? {
+            None => LogFormat::default(),
+            Some((format, source)) => match format.to_lowercase().as_str() {
+                "pretty" => LogFormat::Pretty,
+                "json" => LogFormat::Json,
+                _ => {
+                    return Err(Layers::invalid(
+                        LOG_FORMAT,
+                        &source,
+                        format!("`{format}` is not one of `pretty`, `json`"),
+                    ));
+                }
+            },
+        };
+
+        Ok(Self {
+            jwt_secret: Secret::new(jwt_secret),
+            access_token_lifetime: Duration::from_secs(
+                layers.positive_number(ACCESS_TOKEN_LIFETIME_SECS, 60 * 60)?,
+            ),
+            refresh_token_lifetime: Duration::from_secs(
+                layers.positive_number(REFRESH_TOKEN_LIFETIME_SECS, 30 * 24 * 60 * 60)?,
+            ),
+            audience: layers.string(AUDIENCE)?.map(|(audience, _)| audience),
+            frontend_urls,
+            database_url,
+            database_pool_size,
+            log_format,
+            request_timeout: Duration::from_secs(layers.positive_number(REQUEST_TIMEOUT_SECS, 30)?),
+            max_body_bytes: layers.positive_number(MAX_BODY_BYTES, 2 * 1024 * 1024)? as usize,
+            database_url_variable: layers.variable(DATABASE_URL),
+        })
+    }
+
+    pub fn auth_options(&self) -> AuthOptions {
+        let auth_options = AuthOptions::new(
+            self.jwt_secret.expose().to_string(),
+            self.access_token_lifetime,
+            self.refresh_token_lifetime,
+        );
+        match &self.audience {
+            Some(audience) => auth_options.with_audience(audience.clone()),
+            None => auth_options,
+        }
+    }
+
+    ///Frontend urls, log format and request limits. Everything else can still be changed on the
+    ///returned options.
+    pub fn web_core_options<T>(&self, web_core_state: WebCoreState<T>) -> WebCoreOptions<T>
+    where
+        T: Clone + Send + Sync + 'static,
+    {
+        let request_limits = RequestLimitsOptions::default()
+            .with_timeout(Some(self.request_timeout))
+            .with_max_body_bytes(Some(self.max_body_bytes));
+        self.frontend_urls.iter().fold(
+            WebCoreOptions::new(web_core_state)
+                .with_log_format(self.log_format)
+                .with_request_limits(request_limits),
+            |options, url| options.with_frontend_url(url.clone()),
+        )
+    }
+
+    pub fn database_url(&self) -> Result<&Secret, ConfigError> {
+        self.database_url
+            .as_ref()
+            .ok_or_else(|| ConfigError::Missing {
+                key: DATABASE_URL,
+                variable: self.database_url_variable.clone(),
+            })
+    }
+
+    ///Creates the database when it does not exist, runs the migrations and builds a pool of
+    ///`database_pool_size` connections.
+    #[cfg(feature = "diesel")]
+    pub async fn diesel_pool(
+        &self,
+        migrations: diesel_migrations::EmbeddedMigrations,
+    ) -> Result<
+        ::diesel::r2d2::Pool<::diesel::r2d2::ConnectionManager<::diesel::PgConnection>>,
+        crate::web_core::WebCoreError,
+    > {
+        let database_url = self.database_url()?;
+        crate::diesel::create_pg_pool_with_size(
+            migrations,
+            database_url.expose(),
+            self.database_pool_size,
+        )
+        .await
+        .map_err(crate::web_core::WebCoreError::Database)
+    }
+}
@@ -1,57 +1,289 @@
-use axum::Router;
+use std::{collections::HashSet, sync::Arc, time::Duration};
+
 use axum::http::{
-    HeaderValue, Method,
-    header::{ACCEPT, AUTHORIZATION, CONTENT_TYPE},
+    HeaderName, HeaderValue, Method,
+    header::{ACCEPT, AUTHORIZATION, CONTENT_TYPE, RETRY_AFTER},
+};
+use axum::{
+    Router,
+    body::Body,
+    extract::{MatchedPath, Request},
+    middleware::{self, Next},
 };
-use tower_http::cors::CorsLayer;
+use lambda_http::tower::{Layer, ServiceExt};
+use regex::Regex;
+use tower_http::cors::{AllowOrigin, Any, CorsLayer};
 
 use crate::web_core::WebCoreState;
 
+#[derive(Debug, thiserror::Error)]
+pub enum CorsError {
+    #[error("Invalid CORS origin `{0}`, expected `scheme://host[:port]`.")]
+    InvalidOrigin(String),
+    #[error("Invalid CORS origin pattern `{pattern}` : {source}")]
+    InvalidPattern {
+        pattern: String,
+        #[source]
+        source: regex::Error,
+    },
+    #[error("Invalid CORS header `{0}`.")]
+    InvalidHeader(String),
+    #[error("CORS credentials can not be allowed for any origin.")]
+    CredentialsWithAnyOrigin,
+}
+
+///Which origins may call the api and what they may send and read.
+///
+///Origins are exact (`https://example.com`), wildcard subdomains (`https://*.example.com`)
+///or regular expressions, which must match the whole origin.
+#[derive(Debug, Clone)]
+pub struct CorsOptions {
+    pub origins: Vec<String>,
+    pub origin_patterns: Vec<String>,
+    pub allowed_headers: Vec<String>,
+    pub exposed_headers: Vec<String>,
+    pub methods: Vec<Method>,
+    pub allow_credentials: bool,
+    pub max_age: Option<Duration>,
+    ///Allows every origin. Meant for local development only.
+    pub allow_any_origin: bool,
+}
+
+impl Default for CorsOptions {
+    fn default() -> Self {
+        Self {
+            origins: vec![],
+            origin_patterns: vec![],
+            allowed_headers: [ACCEPT, AUTHORIZATION, CONTENT_TYPE]
+                .map(|x| x.to_string())
+                .into_iter()
+                .chain(["x-request-id", "idempotency-key"].map(String::from))
+                .collect(),
+            exposed_headers: [
+                "x-request-id",
+                RETRY_AFTER.as_str(),
+                "ratelimit-limit",
+                "ratelimit-remaining",
+                "ratelimit-reset",
+                "ratelimit-policy",
+                "idempotent-replayed",
+            ]
+            .map(String::from)
+            .to_vec(),
+            methods: vec![
+                Method::GET,
+                Method::POST,
+                Method::PUT,
+                Method::PATCH,
+                Method::DELETE,
+                Method::OPTIONS,
+            ],
+            allow_credentials: false,
+            max_age: None,
+            allow_any_origin: false,
+        }
+    }
+}
+
+impl CorsOptions {
+    ///Exact origin, wildcard subdomain origin or `*` for any origin.
+    pub fn with_origin(mut self, origin: impl Into<String>) -> Self {
+        let origin = origin.into();
+        if origin == "*" {
+            self.allow_any_origin = true;
+        } else {
+            self.origins.push(origin);
+        }
+        self
+    }
+
+    ///Regular expression which must match the whole origin, eg: `https://pr-\d+\.example\.com`.
+    pub fn with_origin_regex(mut self, pattern: impl Into<String>) -> Self {
+        self.origin_patterns.push(pattern.into());
+        self
+    }
+
+    pub fn with_allowed_header(mut self, header: impl Into<String>) -> Self {
+        self.allowed_headers.push(header.into());
+        self
+    }
+
+    pub fn with_exposed_header(mut self, header: impl Into<String>) -> Self {
+        self.exposed_headers.push(header.into());
+        self
+    }
+
+    pub fn with_methods(mut self, methods: impl IntoIterator<Item = Method>) -> Self {
+        self.methods = methods.into_iter().collect();
+        self
+    }
+
+    pub fn with_credentials(mut self) -> Self {
+        self.allow_credentials = true;
+        self
+    }
+
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    ///Allows every origin. Meant for local development only.
+    pub fn permissive(mut self) -> Self {
+        self.allow_any_origin = true;
+        self
+    }
+
+    pub fn build(&self) -> Result<CorsLayer, CorsError> {
+        let mut cors = CorsLayer::new()
+            .allow_credentials(self.allow_credentials)
+            .allow_headers(parse_headers(&self.allowed_headers)?)
+            .expose_headers(parse_headers(&self.exposed_headers)?)
+            .allow_methods(self.methods.clone());
+        if let Some(max_age) = self.max_age {
+            cors = cors.max_age(max_age);
+        }
+
+        if self.allow_any_origin {
+            if self.allow_credentials {
+                return Err(CorsError::CredentialsWithAnyOrigin);
+            }
+            return Ok(cors.allow_origin(Any));
+        }
+
+        let mut exact = vec![];
+        let mut wildcards = vec![];
+        for origin in &self.origins {
+            match origin.split_once("://*.") {
+                Some((scheme, domain)) => {
+                    let wildcard = parse_origin(&format!("{scheme}://wildcard.{domain}"))
+                        .ok_or_else(|| CorsError::InvalidOrigin(origin.clone()))?;
+                    let suffix = wildcard
+                        .host_str()
+                        .and_then(|x| x.strip_prefix("wildcard"))
+                        .map(String::from)
+                        .ok_or_else(|| CorsError::InvalidOrigin(origin.clone()))?;
+                    wildcards.push((
+                        wildcard.scheme().to_string(),
+                        suffix,
+                        wildcard.port_or_known_default(),
+                    ));
+                }
+                None => {
+                    let normalized = normalize_origin(origin)
+                        .ok_or_else(|| CorsError::InvalidOrigin(origin.clone()))?;
+                    exact.push(normalized);
+                }
+            }
+        }
+        //Anchored, so that `https://example\.com` does not allow `https://example.com.evil.io`.
+        let patterns = self
+            .origin_patterns
+            .iter()
+            .map(|pattern| {
+                Regex::new(&format!("^(?:{pattern})$")).map_err(|source| {
+                    CorsError::InvalidPattern {
+                        pattern: pattern.clone(),
+                        source,
+                    }
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if wildcards.is_empty() && patterns.is_empty() {
+            let exact = exact
+                .iter()
+                .map(|x| HeaderValue::from_str(x))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|_| CorsError::InvalidOrigin(self.origins.join(", ")))?;
+            return Ok(cors.allow_origin(exact));
+        }
+
+        Ok(cors.allow_origin(AllowOrigin::predicate(move |origin, _| {
+            let Ok(origin) = origin.to_str() else {
+                return false;
+            };
+            let matches_wildcard = || {
+                let Some(url) = parse_origin(origin) else {
+                    return false;
+                };
+                wildcards.iter().any(|(scheme, suffix, port)| {
+                    url.scheme() == scheme
+                        && url.port_or_known_default() == *port
+                        && url
+                            .host_str()
+                            .is_some_and(|host| host.ends_with(suffix.as_str()))
+                })
+            };
+            exact.iter().any(|x| x == origin)
+                || matches_wildcard()
+                || patterns.iter().any(|x| x.is_match(origin))
+        })))
+    }
+}
+
+fn parse_headers(headers: &[String]) -> Result<Vec<HeaderName>, CorsError> {
+    headers
+        .iter()
+        .map(|x| HeaderName::try_from(x.as_str()).map_err(|_| CorsError::InvalidHeader(x.clone())))
+        .collect::<Result<_, _>>()
+}
+
+///`https://Example.com/` becomes `https://example.com`. Paths, queries and credentials are not
+///part of an origin and are rejected.
+fn normalize_origin(origin: &str) -> Option<String> {
+    parse_origin(origin).map(|url| url.origin().ascii_serialization())
+}
+
+fn parse_origin(origin: &str) -> Option<url::Url> {
+    let url = url::Url::parse(origin).ok()?;
+    let valid = matches!(url.scheme(), "http" | "https")
+        && url.host_str().is_some()
+        && url.path() == "/"
+        && url.query().is_none()
+        && url.fragment().is_none()
+        && url.username().is_empty()
+        && url.password().is_none();
+    valid.then_some(url)
+}
+
 pub trait WithCorsLayer {
-    fn with_cors_layer(self, frontend_url: Vec<String>) -> Self;
+    fn with_cors_layer(self, cors: CorsLayer) -> Self;
+    ///Leaves out the route templates in `routes`, which answer CORS with their own layer.
+    fn with_cors_layer_except(self, cors: CorsLayer, routes: HashSet<String>) -> Self;
 }
 
 impl<T> WithCorsLayer for Router<WebCoreState<T>>
 where
     T: Clone + Send + Sync + 'static,
 {
-    fn with_cors_layer(self, frontend_url: Vec<String>) -> Self {
-        self.layer(generate_cors(frontend_url))
-    }
-}
-
-pub fn generate_cors(frontend_urls: Vec<String>) -> CorsLayer {
-    #[allow(unused_mut)]
-    let mut cors = CorsLayer::new()
-        .allow_credentials(false)
-        .allow_headers([ACCEPT, AUTHORIZATION, CONTENT_TYPE])
-        .allow_methods([
-            Method::POST,
-            Method::PUT,
-            Method::DELETE,
-            Method::GET,
-            Method::OPTIONS,
-        ]);
-
-    let mut header_values: Vec<HeaderValue> = vec![];
-
-    for url in frontend_urls {
-        let frontend_url = url.trim_end_matches("/");
-        let frontend_url_2 = if frontend_url.contains("https://www.") {
-            frontend_url.replace("https://www.", "https://")
-        } else {
-            frontend_url.replace("https://", "https://www.")
-        };
-
-        header_values.push(frontend_url_2.parse::<HeaderValue>().unwrap());
-        header_values.push(frontend_url.parse::<HeaderValue>().unwrap());
+    fn with_cors_layer(self, cors: CorsLayer) -> Self {
+        self.layer(cors)
     }
 
-    cors = cors.allow_origin(header_values);
-
-    if cfg!(debug_assertions) {
-        use tower_http::cors::Any;
-        cors = cors.allow_origin(Any).allow_credentials(false);
+    fn with_cors_layer_except(self, cors: CorsLayer, routes: HashSet<String>) -> Self {
+        if routes.is_empty() {
+            return self.with_cors_layer(cors);
+        }
+        let routes = Arc::new(routes);
+        self.layer(middleware::from_fn(
+            move |req: Request<Body>, next: Next| {
+                let cors = cors.clone();
+                let routes = routes.clone();
+                async move {
+                    let excepted = req
+                        .extensions()
+                        .get::<MatchedPath>()
+                        .is_some_and(|x| routes.contains(x.as_str()));
+                    if excepted {
+                        return next.run(req).await;
+                    }
+                    match cors.layer(next).oneshot(req).await {
+                        Ok(response) => response,
+                        Err(e) => match e {},
+                    }
+                }
+            },
+        ))
     }
-    return cors;
 }
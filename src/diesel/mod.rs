@@ -13,28 +13,41 @@ pub mod jsonb_data;
 pub async fn create_pg_pool(
     migrations: EmbeddedMigrations,
     database_url: &str,
+) -> Result<Pool<ConnectionManager<PgConnection>>, Error> {
+    create_pg_pool_with_size(migrations, database_url, 10).await
+}
+
+///Same as `create_pg_pool`, with at most `max_size` connections.
+pub async fn create_pg_pool_with_size(
+    migrations: EmbeddedMigrations,
+    database_url: &str,
+    max_size: u32,
 ) -> Result<Pool<ConnectionManager<PgConnection>>, Error> {
     use diesel::Connection;
     use diesel_migrations::MigrationHarness;
 
-    use crate::something_went_wrong;
     let database_exists = sqlx::Postgres::database_exists(database_url)
         .await
-        .map_err(|e| something_went_wrong!("{:?}", e))?;
+        .map_err(|e| {
+            Error::new_something_went_wrong_with_source("Error while checking the database", e)
+        })?;
     if !database_exists {
         sqlx::Postgres::create_database(database_url)
             .await
-            .map_err(|e| something_went_wrong!("{:?}", e))?;
+            .map_err(|e| {
+                Error::new_something_went_wrong_with_source("Error while creating the database", e)
+            })?;
     }
 
-    let mut setup_conn =
-        PgConnection::establish(database_url).map_err(|e| something_went_wrong!("{:?}", e))?;
-    setup_conn
-        .run_pending_migrations(migrations)
-        .map_err(|e| something_went_wrong!("{:?}", e))?;
+    let mut setup_conn = PgConnection::establish(database_url).map_err(|e| {
+        Error::new_something_went_wrong_with_source("Error while connecting to the database", e)
+    })?;
+    setup_conn.run_pending_migrations(migrations).map_err(|e| {
+        Error::new_something_went_wrong_with_source("Error while running migrations", e)
+    })?;
     drop(setup_conn);
 
     let manager = ConnectionManager::<PgConnection>::new(database_url);
-    let pool_builder = diesel::r2d2::Pool::builder();
+    let pool_builder = diesel::r2d2::Pool::builder().max_size(max_size);
     Ok(pool_builder.build(manager)?)
 }
@@ -2,9 +2,17 @@ use axum::{Json, response::IntoResponse};
 use http::StatusCode;
 use serde::ser::SerializeStruct;
 
-#[derive(Debug, serde::Deserialize)]
+use super::code::CoreErrorCode;
+
+pub const UNAUTHORIZED_MESSAGE: &str = "Unauthorized";
+
+#[derive(Debug, Clone, serde::Deserialize)]
 pub struct AuthenticationError {
+    #[serde(default)]
+    pub error: String,
     pub error_details: String,
+    #[serde(default)]
+    pub code: String,
 }
 
 impl serde::Serialize for AuthenticationError {
@@ -12,9 +20,10 @@ impl serde::Serialize for AuthenticationError {
     where
         S: serde::Serializer,
     {
-        let field_count = if cfg!(debug_assertions) { 2 } else { 1 };
+        let field_count = if cfg!(debug_assertions) { 3 } else { 2 };
         let mut state = serializer.serialize_struct("AuthenticationError", field_count)?;
-        state.serialize_field("error", "Unauthorized")?;
+        state.serialize_field("error", &self.error)?;
+        state.serialize_field("code", &self.code)?;
         if cfg!(debug_assertions) {
             state.serialize_field("error_details", &self.error_details)?;
         }
@@ -31,7 +40,9 @@ impl IntoResponse for AuthenticationError {
 impl AuthenticationError {
     pub fn new(error_details: impl std::fmt::Display) -> Self {
         Self {
+            error: UNAUTHORIZED_MESSAGE.to_string(),
             error_details: error_details.to_string(),
+            code: CoreErrorCode::Unauthorized.to_string(),
         }
     }
 }
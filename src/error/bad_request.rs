@@ -2,10 +2,14 @@ use std::collections::HashMap;
 
 use serde_json::Value;
 
+use super::code::CoreErrorCode;
+
 #[derive(Debug, Clone, serde::Deserialize)]
 pub struct BadRequestError {
     pub error: String,
     #[serde(default)]
+    pub code: String,
+    #[serde(default)]
     pub data: HashMap<String, String>,
     pub _data: Option<Value>,
 }
@@ -14,6 +18,7 @@ impl BadRequestError {
     pub fn new(error: String) -> Self {
         Self {
             error,
+            code: CoreErrorCode::BadRequest.to_string(),
             data: HashMap::new(),
             _data: None,
         }
@@ -22,6 +27,7 @@ impl BadRequestError {
     pub fn new_with_data(error: String, data: Value) -> Self {
         Self {
             error,
+            code: CoreErrorCode::BadRequest.to_string(),
             data: HashMap::new(),
             _data: Some(data),
         }
@@ -37,10 +43,11 @@ impl serde::Serialize for BadRequestError {
         S: serde::Serializer,
     {
         let has_data = !self.data.is_empty() || self._data.is_some();
-        let field_count = if has_data { 2 } else { 1 };
+        let field_count = if has_data { 3 } else { 2 };
 
         let mut state = serializer.serialize_struct("BadRequestError", field_count)?;
         state.serialize_field("error", &self.error)?;
+        state.serialize_field("code", &self.code)?;
 
         if !self.data.is_empty() {
             state.serialize_field("data", &self.data)?;
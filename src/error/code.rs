@@ -0,0 +1,110 @@
+use std::borrow::Cow;
+
+///Stable, machine readable identifier of an error. Serialized as `code` in every error response.
+///Implement this for your own enums to use them with `Error::with_code` and the error macros.
+pub trait ErrorCode {
+    fn code(&self) -> Cow<'static, str>;
+}
+
+impl ErrorCode for &'static str {
+    fn code(&self) -> Cow<'static, str> {
+        Cow::Borrowed(self)
+    }
+}
+
+impl ErrorCode for String {
+    fn code(&self) -> Cow<'static, str> {
+        Cow::Owned(self.clone())
+    }
+}
+
+impl ErrorCode for Cow<'static, str> {
+    fn code(&self) -> Cow<'static, str> {
+        self.clone()
+    }
+}
+
+///Codes used by web-core itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum_macros::IntoStaticStr, strum_macros::Display)]
+pub enum CoreErrorCode {
+    #[strum(serialize = "bad_request")]
+    BadRequest,
+    #[strum(serialize = "validation.failed")]
+    ValidationFailed,
+    #[strum(serialize = "validation.invalid")]
+    InvalidField,
+    ///Not namespaced, as it was the code of `validate_phone` before codes existed and
+    ///`Error::from(ValidationError)` uses it as the field key.
+    #[strum(serialize = "invalid_phone")]
+    InvalidPhoneNumber,
+    #[strum(serialize = "internal_error")]
+    InternalError,
+    #[strum(serialize = "auth.unauthorized")]
+    Unauthorized,
+    #[strum(serialize = "auth.missing_token")]
+    MissingToken,
+    #[strum(serialize = "auth.token_invalid")]
+    TokenInvalid,
+    #[strum(serialize = "auth.token_expired")]
+    TokenExpired,
+    #[strum(serialize = "auth.token_purpose_mismatch")]
+    TokenPurposeMismatch,
+    #[strum(serialize = "auth.missing_role")]
+    MissingRole,
+    #[strum(serialize = "not_found")]
+    NotFound,
+    #[strum(serialize = "forbidden")]
+    Forbidden,
+    #[strum(serialize = "conflict")]
+    Conflict,
+    #[strum(serialize = "db.unique_violation")]
+    UniqueViolation,
+    #[strum(serialize = "db.foreign_key_violation")]
+    ForeignKeyViolation,
+    #[strum(serialize = "db.check_violation")]
+    CheckViolation,
+    #[strum(serialize = "db.serialization_failure")]
+    SerializationFailure,
+    #[strum(serialize = "gone")]
+    Gone,
+    #[strum(serialize = "unprocessable_entity")]
+    UnprocessableEntity,
+    #[strum(serialize = "rate_limited")]
+    TooManyRequests,
+    #[strum(serialize = "service_unavailable")]
+    ServiceUnavailable,
+    #[strum(serialize = "upstream.timeout")]
+    UpstreamTimeout,
+    #[strum(serialize = "payload_too_large")]
+    PayloadTooLarge,
+    #[strum(serialize = "request.timeout")]
+    RequestTimeout,
+    #[strum(serialize = "service.overloaded")]
+    Overloaded,
+    #[strum(serialize = "request.invalid_json")]
+    InvalidJson,
+    #[strum(serialize = "request.invalid_path")]
+    InvalidPath,
+    #[strum(serialize = "request.invalid_query")]
+    InvalidQuery,
+    #[strum(serialize = "request.missing_header")]
+    MissingHeader,
+    #[strum(serialize = "request.invalid_header")]
+    InvalidHeader,
+    #[strum(serialize = "idempotency.in_flight")]
+    IdempotencyInFlight,
+    #[strum(serialize = "idempotency.key_reused")]
+    IdempotencyKeyReused,
+}
+
+impl CoreErrorCode {
+    pub fn as_str(&self) -> &'static str {
+        self.into()
+    }
+}
+
+impl ErrorCode for CoreErrorCode {
+    fn code(&self) -> Cow<'static, str> {
+        Cow::Borrowed(self.as_str())
+    }
+}
@@ -0,0 +1,50 @@
+use std::{collections::HashMap, time::Duration};
+
+use super::{
+    Error, code::CoreErrorCode, conflict::ConflictError,
+    unprocessable_entity::UnprocessableEntityError,
+};
+
+///Replaces the message of an error raised by a constraint found in `messages`,
+///see `WebCoreOptions::with_constraint_message`.
+pub(crate) fn apply_constraint_message(error: &mut Error, messages: &HashMap<String, String>) {
+    let constraint = match &*error {
+        Error::Conflict(ConflictError { constraint, .. })
+        | Error::UnprocessableEntity(UnprocessableEntityError { constraint, .. }) => constraint,
+        _ => return,
+    };
+    if let Some(message) = constraint.as_deref().and_then(|x| messages.get(x)) {
+        *error.message_mut() = message.clone();
+    }
+}
+
+fn message(constraint: Option<&str>, kind: &str) -> String {
+    match constraint {
+        Some(constraint) => format!("Violates {kind} constraint `{constraint}`."),
+        None => format!("Violates a {kind} constraint."),
+    }
+}
+
+pub(crate) fn unique_violation(constraint: Option<&str>) -> Error {
+    let mut error = ConflictError::new(message(constraint, "unique"));
+    error.constraint = constraint.map(String::from);
+    Error::Conflict(error).with_code(CoreErrorCode::UniqueViolation)
+}
+
+pub(crate) fn foreign_key_violation(constraint: Option<&str>) -> Error {
+    let mut error = UnprocessableEntityError::new(message(constraint, "foreign key"));
+    error.constraint = constraint.map(String::from);
+    Error::UnprocessableEntity(error).with_code(CoreErrorCode::ForeignKeyViolation)
+}
+
+pub(crate) fn check_violation(constraint: Option<&str>) -> Error {
+    let mut error = UnprocessableEntityError::new(message(constraint, "check"));
+    error.constraint = constraint.map(String::from);
+    Error::UnprocessableEntity(error).with_code(CoreErrorCode::CheckViolation)
+}
+
+///The transaction lost a race with another one and can be retried as is.
+pub(crate) fn serialization_failure() -> Error {
+    Error::new_service_unavailable("", Some(Duration::from_secs(1)))
+        .with_code(CoreErrorCode::SerializationFailure)
+}
@@ -1,10 +1,114 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 use serde::ser::SerializeStruct;
+use serde_json::Value;
+use validator_async::{ValidationError, ValidationErrors, ValidationErrorsKind};
 
-#[derive(Debug, serde::Deserialize)]
+use super::code::{CoreErrorCode, ErrorCode};
+
+pub const VALIDATION_FAILED_MESSAGE: &str = "Validation failed";
+///Message of validation errors without one. Translate it per code with a `MessageCatalog`.
+pub const INVALID_FIELD_MESSAGE: &str = "Invalid value";
+
+///Key used by validator for errors of the whole struct (eg: `#[validate(schema(...))]`).
+const SCHEMA_ERRORS_KEY: &str = "__all__";
+
+#[derive(Debug, Clone, serde::Deserialize)]
 pub struct FieldValidationErrors {
-    pub fields: HashMap<String, String>,
+    #[serde(default)]
+    pub error: String,
+    ///Errors of each field, keyed by path. eg: `email`, `address.zip`, `items[2].qty`.
+    pub fields: HashMap<String, Vec<FieldError>>,
+    ///Errors of the whole request rather than of a single field.
+    #[serde(default)]
+    pub schema_errors: Vec<FieldError>,
+    #[serde(default)]
+    pub code: String,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct FieldError {
+    pub code: String,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub params: BTreeMap<String, Value>,
+}
+
+impl FieldError {
+    pub fn new(code: impl ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code: code.code().into_owned(),
+            message: message.into(),
+            params: BTreeMap::new(),
+        }
+    }
+}
+
+impl From<&ValidationError> for FieldError {
+    ///The `value` param is left out so that rejected input (eg: passwords) is not echoed back.
+    ///The `Display` of validator is not used either, as it prints every param.
+    fn from(error: &ValidationError) -> Self {
+        Self {
+            code: error.code.to_string(),
+            message: error
+                .message
+                .as_ref()
+                .map(|x| x.to_string())
+                .unwrap_or_else(|| INVALID_FIELD_MESSAGE.to_string()),
+            params: error
+                .params
+                .iter()
+                .filter(|(key, _)| *key != "value")
+                .map(|(key, value)| (key.to_string(), value.clone()))
+                .collect(),
+        }
+    }
+}
+
+impl FieldValidationErrors {
+    pub fn new(fields: HashMap<String, Vec<FieldError>>, schema_errors: Vec<FieldError>) -> Self {
+        Self {
+            error: VALIDATION_FAILED_MESSAGE.to_string(),
+            fields,
+            schema_errors,
+            code: CoreErrorCode::ValidationFailed.to_string(),
+        }
+    }
+
+    fn collect(&mut self, path: &str, errors: &ValidationErrors) {
+        for (field, kind) in errors.errors() {
+            let field_path = match (path.is_empty(), field.as_ref()) {
+                (true, SCHEMA_ERRORS_KEY) => String::new(),
+                (false, SCHEMA_ERRORS_KEY) => path.to_string(),
+                (true, _) => field.to_string(),
+                (false, _) => format!("{path}.{field}"),
+            };
+            match kind {
+                ValidationErrorsKind::Field(errors) => {
+                    let errors = errors.iter().map(FieldError::from);
+                    if field_path.is_empty() {
+                        self.schema_errors.extend(errors);
+                    } else {
+                        self.fields.entry(field_path).or_default().extend(errors);
+                    }
+                }
+                ValidationErrorsKind::Struct(errors) => self.collect(&field_path, errors),
+                ValidationErrorsKind::List(items) => {
+                    for (index, errors) in items {
+                        self.collect(&format!("{field_path}[{index}]"), errors);
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl From<&ValidationErrors> for FieldValidationErrors {
+    fn from(errors: &ValidationErrors) -> Self {
+        let mut field_validation_errors = FieldValidationErrors::new(HashMap::new(), vec![]);
+        field_validation_errors.collect("", errors);
+        field_validation_errors
+    }
 }
 
 impl serde::Serialize for FieldValidationErrors {
@@ -12,9 +116,14 @@ impl serde::Serialize for FieldValidationErrors {
     where
         S: serde::Serializer,
     {
-        let mut state = serializer.serialize_struct("FieldValidationErrors", 2)?;
-        state.serialize_field("error", "Validation failed")?;
+        let field_count = if self.schema_errors.is_empty() { 3 } else { 4 };
+        let mut state = serializer.serialize_struct("FieldValidationErrors", field_count)?;
+        state.serialize_field("error", &self.error)?;
+        state.serialize_field("code", &self.code)?;
         state.serialize_field("fields", &self.fields)?;
+        if !self.schema_errors.is_empty() {
+            state.serialize_field("schema_errors", &self.schema_errors)?;
+        }
         state.end()
     }
 }
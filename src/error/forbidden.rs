@@ -0,0 +1,26 @@
+use axum::{Json, response::IntoResponse};
+use http::StatusCode;
+
+use super::code::CoreErrorCode;
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ForbiddenError {
+    pub error: String,
+    #[serde(default)]
+    pub code: String,
+}
+
+impl IntoResponse for ForbiddenError {
+    fn into_response(self) -> axum::response::Response {
+        (StatusCode::FORBIDDEN, Json(serde_json::json!(self))).into_response()
+    }
+}
+
+impl ForbiddenError {
+    pub fn new(error: String) -> Self {
+        Self {
+            error,
+            code: CoreErrorCode::Forbidden.to_string(),
+        }
+    }
+}
@@ -0,0 +1,26 @@
+use axum::{Json, response::IntoResponse};
+use http::StatusCode;
+
+use super::code::CoreErrorCode;
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct GoneError {
+    pub error: String,
+    #[serde(default)]
+    pub code: String,
+}
+
+impl IntoResponse for GoneError {
+    fn into_response(self) -> axum::response::Response {
+        (StatusCode::GONE, Json(serde_json::json!(self))).into_response()
+    }
+}
+
+impl GoneError {
+    pub fn new(error: String) -> Self {
+        Self {
+            error,
+            code: CoreErrorCode::Gone.to_string(),
+        }
+    }
+}
@@ -0,0 +1,149 @@
+use std::collections::{BTreeMap, HashMap};
+
+use serde_json::Value;
+
+use super::{
+    authentication::UNAUTHORIZED_MESSAGE, code::CoreErrorCode,
+    field_validation::VALIDATION_FAILED_MESSAGE,
+    something_went_wrong::SOMETHING_WENT_WRONG_MESSAGE,
+};
+use crate::validators::phone_number_validator::INVALID_PHONE_NUMBER_MESSAGE;
+
+pub const DEFAULT_LOCALE: &str = "en";
+
+///Error and validation messages keyed by locale and error code.
+///Messages of the fallback locale are used when the requested locale has no translation.
+#[derive(Debug, Clone)]
+pub struct MessageCatalog {
+    fallback_locale: String,
+    messages: HashMap<String, HashMap<String, String>>,
+}
+
+impl MessageCatalog {
+    ///Creates a catalog containing the english messages of web-core.
+    pub fn new() -> Self {
+        Self {
+            fallback_locale: DEFAULT_LOCALE.to_string(),
+            messages: HashMap::new(),
+        }
+        .with_message(
+            DEFAULT_LOCALE,
+            CoreErrorCode::ValidationFailed,
+            VALIDATION_FAILED_MESSAGE,
+        )
+        .with_message(
+            DEFAULT_LOCALE,
+            CoreErrorCode::InternalError,
+            SOMETHING_WENT_WRONG_MESSAGE,
+        )
+        .with_message(
+            DEFAULT_LOCALE,
+            CoreErrorCode::Unauthorized,
+            UNAUTHORIZED_MESSAGE,
+        )
+        .with_message(
+            DEFAULT_LOCALE,
+            CoreErrorCode::InvalidPhoneNumber,
+            INVALID_PHONE_NUMBER_MESSAGE,
+        )
+    }
+
+    pub fn with_fallback_locale(mut self, locale: &str) -> Self {
+        self.fallback_locale = normalize_locale(locale);
+        self
+    }
+
+    pub fn with_message(mut self, locale: &str, code: impl ToString, message: &str) -> Self {
+        self.register(locale, code, message);
+        self
+    }
+
+    pub fn with_messages<C, M>(
+        mut self,
+        locale: &str,
+        messages: impl IntoIterator<Item = (C, M)>,
+    ) -> Self
+    where
+        C: ToString,
+        M: AsRef<str>,
+    {
+        for (code, message) in messages {
+            self.register(locale, code, message.as_ref());
+        }
+        self
+    }
+
+    pub fn register(&mut self, locale: &str, code: impl ToString, message: &str) {
+        self.messages
+            .entry(normalize_locale(locale))
+            .or_default()
+            .insert(code.to_string(), message.to_string());
+    }
+
+    ///Message for the code in the locale, its language (`de` for `de-AT`) or the fallback locale.
+    pub fn message(&self, locale: &str, code: &str) -> Option<&str> {
+        let locale = normalize_locale(locale);
+        let language = locale.split('-').next().unwrap_or_default();
+        [locale.as_str(), language, self.fallback_locale.as_str()]
+            .into_iter()
+            .find_map(|locale| self.messages.get(locale)?.get(code))
+            .map(|x| x.as_str())
+    }
+
+    ///Message for the code with `{param}` placeholders replaced by the params.
+    pub fn format(
+        &self,
+        locale: &str,
+        code: &str,
+        params: &BTreeMap<String, Value>,
+    ) -> Option<String> {
+        let mut message = self.message(locale, code)?.to_string();
+        for (key, value) in params {
+            let value = match value {
+                Value::String(x) => x.clone(),
+                x => x.to_string(),
+            };
+            message = message.replace(&format!("{{{key}}}"), &value);
+        }
+        Some(message)
+    }
+
+    ///Picks the best locale of the catalog for an `Accept-Language` header value.
+    pub fn negotiate(&self, accept_language: Option<&str>) -> String {
+        let mut requested: Vec<(String, f32)> = accept_language
+            .unwrap_or_default()
+            .split(',')
+            .filter_map(|x| {
+                let mut parts = x.split(';');
+                let locale = normalize_locale(parts.next()?);
+                let quality = parts
+                    .find_map(|x| x.trim().strip_prefix("q="))
+                    .and_then(|x| x.parse::<f32>().ok())
+                    .unwrap_or(1.0);
+                (!locale.is_empty() && quality > 0.0).then_some((locale, quality))
+            })
+            .collect();
+        requested.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+        for (locale, _) in requested {
+            if self.messages.contains_key(&locale) {
+                return locale;
+            }
+            let language = locale.split('-').next().unwrap_or_default();
+            if self.messages.contains_key(language) {
+                return language.to_string();
+            }
+        }
+        self.fallback_locale.clone()
+    }
+}
+
+impl Default for MessageCatalog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn normalize_locale(locale: &str) -> String {
+    locale.trim().replace('_', "-").to_lowercase()
+}
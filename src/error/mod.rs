@@ -1,30 +1,68 @@
 pub mod authentication;
 pub mod bad_request;
+pub mod code;
+pub mod conflict;
+pub mod constraints;
 pub mod field_validation;
+pub mod forbidden;
+pub mod gone;
+pub mod localization;
 pub mod not_found;
+pub mod payload_too_large;
+pub mod problem_details;
+pub mod reporting;
+pub mod request_timeout;
+pub mod service_unavailable;
 pub mod something_went_wrong;
+pub mod too_many_requests;
+pub mod unprocessable_entity;
 
-use std::{collections::HashMap, fmt::Debug};
+use std::{collections::HashMap, fmt::Debug, time::Duration};
 
 use authentication::AuthenticationError;
 use axum::{
     Json,
+    extract::rejection::{JsonRejection, PathRejection, QueryRejection},
     http::StatusCode,
     response::{IntoResponse, Response},
 };
+use axum_extra::typed_header::{TypedHeaderRejection, TypedHeaderRejectionReason};
 use bad_request::BadRequestError;
-use field_validation::FieldValidationErrors;
+use code::{CoreErrorCode, ErrorCode};
+use conflict::ConflictError;
+use field_validation::{FieldError, FieldValidationErrors};
+use forbidden::ForbiddenError;
+use gone::GoneError;
+use http::header::AUTHORIZATION;
+use localization::MessageCatalog;
 use not_found::NotFoundError;
+use payload_too_large::PayloadTooLargeError;
+use problem_details::ProblemDetails;
+use request_timeout::RequestTimeoutError;
+use service_unavailable::ServiceUnavailableError;
 use something_went_wrong::SomethingWentWrong;
+use too_many_requests::TooManyRequestsError;
+use unprocessable_entity::UnprocessableEntityError;
 use validator_async::{ValidationError, ValidationErrors};
 
-#[derive(Debug)]
+use crate::middleware::error_rendering::is_reporting;
+
+///The largest variants are boxed, so that `Result<_, Error>` stays small.
+#[derive(Debug, Clone)]
 pub enum Error {
     FieldValidationError(FieldValidationErrors),
-    BadRequestError(BadRequestError),
-    SomethingWentWrong(SomethingWentWrong),
+    BadRequestError(Box<BadRequestError>),
+    SomethingWentWrong(Box<SomethingWentWrong>),
     AuthenticationFailure(AuthenticationError),
     NotFound(NotFoundError),
+    Forbidden(ForbiddenError),
+    Conflict(ConflictError),
+    Gone(GoneError),
+    UnprocessableEntity(UnprocessableEntityError),
+    TooManyRequests(TooManyRequestsError),
+    ServiceUnavailable(ServiceUnavailableError),
+    PayloadTooLarge(PayloadTooLargeError),
+    RequestTimeout(RequestTimeoutError),
 }
 
 impl Error {
@@ -36,30 +74,237 @@ impl Error {
 
     pub fn new_field_validation_error(field: &str, error: &str) -> Error {
         let mut hash_map = HashMap::new();
-        hash_map.insert(field.into(), error.into());
-        let field_validation_errors = FieldValidationErrors { fields: hash_map };
+        hash_map.insert(
+            field.into(),
+            vec![FieldError::new(CoreErrorCode::InvalidField, error)],
+        );
+        let field_validation_errors = FieldValidationErrors::new(hash_map, vec![]);
         Error::FieldValidationError(field_validation_errors)
     }
 
     ///Error info is not shown in api response. It is only printed to console.
     pub fn new_something_went_wrong(error_info: String) -> Error {
-        let something_went_wrong = SomethingWentWrong::new(error_info);
-        Error::SomethingWentWrong(something_went_wrong)
+        let mut something_went_wrong = SomethingWentWrong::new(());
+        something_went_wrong.error_details = error_info;
+        Error::SomethingWentWrong(Box::new(something_went_wrong))
+    }
+
+    ///Keeps the source error and its chain for reporting. Only the error id is shown in api response.
+    pub fn new_something_went_wrong_with_source(
+        context: impl std::fmt::Display,
+        source: impl Into<Box<dyn std::error::Error + Send + Sync>>,
+    ) -> Error {
+        Error::SomethingWentWrong(Box::new(SomethingWentWrong::with_source(context, source)))
     }
 
     pub fn bad_request_error(message: &str) -> Error {
-        Error::BadRequestError(BadRequestError::new(message.into()))
+        Error::BadRequestError(Box::new(BadRequestError::new(message.into())))
     }
 
     pub fn new_not_found(message: &str) -> Error {
         let not_found_error = NotFoundError::new(message.to_string());
         Error::NotFound(not_found_error)
     }
+
+    pub fn new_forbidden(message: &str) -> Error {
+        Error::Forbidden(ForbiddenError::new(message.to_string()))
+    }
+
+    pub fn new_conflict(message: &str) -> Error {
+        Error::Conflict(ConflictError::new(message.to_string()))
+    }
+
+    pub fn new_gone(message: &str) -> Error {
+        Error::Gone(GoneError::new(message.to_string()))
+    }
+
+    pub fn new_unprocessable_entity(message: &str) -> Error {
+        Error::UnprocessableEntity(UnprocessableEntityError::new(message.to_string()))
+    }
+
+    ///`retry_after` is sent as the `Retry-After` header in seconds.
+    pub fn new_too_many_requests(message: &str, retry_after: Option<Duration>) -> Error {
+        Error::TooManyRequests(TooManyRequestsError::new(message.to_string(), retry_after))
+    }
+
+    ///`retry_after` is sent as the `Retry-After` header in seconds.
+    pub fn new_service_unavailable(message: &str, retry_after: Option<Duration>) -> Error {
+        Error::ServiceUnavailable(ServiceUnavailableError::new(
+            message.to_string(),
+            retry_after,
+        ))
+    }
+
+    pub fn new_payload_too_large(message: &str) -> Error {
+        Error::PayloadTooLarge(PayloadTooLargeError::new(message.to_string()))
+    }
+
+    pub fn new_request_timeout(message: &str) -> Error {
+        Error::RequestTimeout(RequestTimeoutError::new(message.to_string()))
+    }
+
+    pub fn code(&self) -> &str {
+        match self {
+            Error::FieldValidationError(FieldValidationErrors { code, .. })
+            | Error::AuthenticationFailure(AuthenticationError { code, .. })
+            | Error::NotFound(NotFoundError { code, .. })
+            | Error::Forbidden(ForbiddenError { code, .. })
+            | Error::Conflict(ConflictError { code, .. })
+            | Error::Gone(GoneError { code, .. })
+            | Error::UnprocessableEntity(UnprocessableEntityError { code, .. })
+            | Error::TooManyRequests(TooManyRequestsError { code, .. })
+            | Error::ServiceUnavailable(ServiceUnavailableError { code, .. })
+            | Error::PayloadTooLarge(PayloadTooLargeError { code, .. })
+            | Error::RequestTimeout(RequestTimeoutError { code, .. }) => code,
+            Error::BadRequestError(bad_request_error) => &bad_request_error.code,
+            Error::SomethingWentWrong(something_went_wrong) => &something_went_wrong.code,
+        }
+    }
+
+    ///Replaces the default code of the error, eg: `bad_request!("..").with_code(MyCode::Expired)`.
+    pub fn with_code(mut self, error_code: impl ErrorCode) -> Self {
+        match &mut self {
+            Error::FieldValidationError(FieldValidationErrors { code, .. })
+            | Error::AuthenticationFailure(AuthenticationError { code, .. })
+            | Error::NotFound(NotFoundError { code, .. })
+            | Error::Forbidden(ForbiddenError { code, .. })
+            | Error::Conflict(ConflictError { code, .. })
+            | Error::Gone(GoneError { code, .. })
+            | Error::UnprocessableEntity(UnprocessableEntityError { code, .. })
+            | Error::TooManyRequests(TooManyRequestsError { code, .. })
+            | Error::ServiceUnavailable(ServiceUnavailableError { code, .. })
+            | Error::PayloadTooLarge(PayloadTooLargeError { code, .. })
+            | Error::RequestTimeout(RequestTimeoutError { code, .. }) => {
+                *code = error_code.code().into_owned();
+            }
+            Error::BadRequestError(bad_request_error) => {
+                bad_request_error.code = error_code.code().into_owned();
+            }
+            Error::SomethingWentWrong(something_went_wrong) => {
+                something_went_wrong.code = error_code.code().into_owned();
+            }
+        }
+        self
+    }
+
+    ///Rejections with a server side status (eg: missing path params) are treated as bugs.
+    fn from_rejection(status: StatusCode, body_text: String, code: CoreErrorCode) -> Error {
+        match status {
+            StatusCode::PAYLOAD_TOO_LARGE => Error::new_payload_too_large(&body_text),
+            StatusCode::UNPROCESSABLE_ENTITY => {
+                Error::new_unprocessable_entity(&body_text).with_code(code)
+            }
+            status if status.is_server_error() => Error::new_something_went_wrong(body_text),
+            _ => Error::bad_request_error(&body_text).with_code(code),
+        }
+    }
 }
 
-impl IntoResponse for Error {
-    fn into_response(self) -> Response {
+///How `Error` responses are rendered by `with_web_core`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ErrorFormat {
+    ///The `{"error": ...}` shapes of the individual error types.
+    #[default]
+    Json,
+    ///RFC 9457 `application/problem+json`.
+    ProblemJson,
+}
+
+impl Error {
+    pub fn status_code(&self) -> StatusCode {
         match self {
+            Error::FieldValidationError(_) => StatusCode::BAD_REQUEST,
+            Error::BadRequestError(_) => StatusCode::BAD_REQUEST,
+            Error::SomethingWentWrong(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            Error::AuthenticationFailure(_) => StatusCode::UNAUTHORIZED,
+            Error::NotFound(_) => StatusCode::NOT_FOUND,
+            Error::Forbidden(_) => StatusCode::FORBIDDEN,
+            Error::Conflict(_) => StatusCode::CONFLICT,
+            Error::Gone(_) => StatusCode::GONE,
+            Error::UnprocessableEntity(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            Error::TooManyRequests(_) => StatusCode::TOO_MANY_REQUESTS,
+            Error::ServiceUnavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+            Error::PayloadTooLarge(_) => StatusCode::PAYLOAD_TOO_LARGE,
+            Error::RequestTimeout(_) => StatusCode::REQUEST_TIMEOUT,
+        }
+    }
+
+    pub fn to_problem_details(&self) -> ProblemDetails {
+        let problem_details =
+            ProblemDetails::new(self.status_code()).with_extension("code", self.code());
+        match self {
+            Error::FieldValidationError(field_validation_errors) => problem_details
+                .with_detail(field_validation_errors.error.clone())
+                .with_extension("errors", serde_json::json!(field_validation_errors.fields))
+                .with_extension(
+                    "schema_errors",
+                    serde_json::json!(field_validation_errors.schema_errors),
+                ),
+            Error::BadRequestError(bad_request_error) => {
+                let problem_details = problem_details.with_detail(bad_request_error.error.clone());
+                match &bad_request_error._data {
+                    Some(data) => problem_details.with_extension("data", data.clone()),
+                    None if !bad_request_error.data.is_empty() => problem_details
+                        .with_extension("data", serde_json::json!(bad_request_error.data)),
+                    None => problem_details,
+                }
+            }
+            Error::SomethingWentWrong(something_went_wrong) => {
+                let mut problem_details = problem_details
+                    .with_detail(something_went_wrong.error.clone())
+                    .with_extension("error_id", something_went_wrong.error_id.clone());
+                if let Some(request_id) = &something_went_wrong.request_id {
+                    problem_details =
+                        problem_details.with_extension("request_id", request_id.clone());
+                }
+                if cfg!(debug_assertions) {
+                    problem_details
+                        .with_extension("error_details", something_went_wrong.error_details.clone())
+                } else {
+                    problem_details
+                }
+            }
+            Error::AuthenticationFailure(authentication_error) => {
+                let problem_details =
+                    problem_details.with_detail(authentication_error.error.clone());
+                if cfg!(debug_assertions) {
+                    problem_details
+                        .with_extension("error_details", authentication_error.error_details.clone())
+                } else {
+                    problem_details
+                }
+            }
+            Error::TooManyRequests(TooManyRequestsError {
+                error, retry_after, ..
+            })
+            | Error::ServiceUnavailable(ServiceUnavailableError {
+                error, retry_after, ..
+            }) => {
+                let problem_details = with_optional_detail(problem_details, error);
+                match retry_after {
+                    Some(retry_after) => {
+                        problem_details.with_extension("retry_after", *retry_after)
+                    }
+                    None => problem_details,
+                }
+            }
+            Error::NotFound(NotFoundError { error, .. })
+            | Error::Forbidden(ForbiddenError { error, .. })
+            | Error::Conflict(ConflictError { error, .. })
+            | Error::Gone(GoneError { error, .. })
+            | Error::UnprocessableEntity(UnprocessableEntityError { error, .. })
+            | Error::PayloadTooLarge(PayloadTooLargeError { error, .. })
+            | Error::RequestTimeout(RequestTimeoutError { error, .. }) => {
+                with_optional_detail(problem_details, error)
+            }
+        }
+    }
+}
+
+impl Error {
+    ///Renders the error in the `ErrorFormat::Json` shape.
+    pub fn to_json_response(&self) -> Response {
+        match self.clone() {
             Error::FieldValidationError(field_validation_errors) => {
                 (StatusCode::BAD_REQUEST, Json(field_validation_errors)).into_response()
             }
@@ -68,60 +313,345 @@ impl IntoResponse for Error {
                 Json(serde_json::json!(bad_request_error)),
             )
                 .into_response(),
-            Error::SomethingWentWrong(something_went_wrong) => {
-                something_went_wrong.print_error();
-                something_went_wrong.into_response()
-            }
+            Error::SomethingWentWrong(something_went_wrong) => something_went_wrong.into_response(),
             Error::AuthenticationFailure(authentication_error) => {
                 authentication_error.into_response()
             }
             Error::NotFound(not_found_error) => not_found_error.into_response(),
+            Error::Forbidden(forbidden_error) => forbidden_error.into_response(),
+            Error::Conflict(conflict_error) => conflict_error.into_response(),
+            Error::Gone(gone_error) => gone_error.into_response(),
+            Error::UnprocessableEntity(unprocessable_entity_error) => {
+                unprocessable_entity_error.into_response()
+            }
+            Error::TooManyRequests(too_many_requests_error) => {
+                too_many_requests_error.into_response()
+            }
+            Error::ServiceUnavailable(service_unavailable_error) => {
+                service_unavailable_error.into_response()
+            }
+            Error::PayloadTooLarge(payload_too_large_error) => {
+                payload_too_large_error.into_response()
+            }
+            Error::RequestTimeout(request_timeout_error) => request_timeout_error.into_response(),
+        }
+    }
+
+    ///Translates the message of the error and of its fields using the codes.
+    ///Messages without a translation are left as they are.
+    pub fn localize(&mut self, catalog: &MessageCatalog, locale: &str) {
+        if let Some(message) = catalog.message(locale, self.code()) {
+            *self.message_mut() = message.to_string();
+        }
+        if let Error::FieldValidationError(field_validation_errors) = self {
+            let field_errors = field_validation_errors.fields.values_mut().flatten();
+            for field_error in field_errors.chain(&mut field_validation_errors.schema_errors) {
+                if let Some(message) =
+                    catalog.format(locale, &field_error.code, &field_error.params)
+                {
+                    field_error.message = message;
+                }
+            }
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            Error::FieldValidationError(FieldValidationErrors { error, .. })
+            | Error::AuthenticationFailure(AuthenticationError { error, .. })
+            | Error::NotFound(NotFoundError { error, .. })
+            | Error::Forbidden(ForbiddenError { error, .. })
+            | Error::Conflict(ConflictError { error, .. })
+            | Error::Gone(GoneError { error, .. })
+            | Error::UnprocessableEntity(UnprocessableEntityError { error, .. })
+            | Error::TooManyRequests(TooManyRequestsError { error, .. })
+            | Error::ServiceUnavailable(ServiceUnavailableError { error, .. })
+            | Error::PayloadTooLarge(PayloadTooLargeError { error, .. })
+            | Error::RequestTimeout(RequestTimeoutError { error, .. }) => error,
+            Error::BadRequestError(bad_request_error) => &bad_request_error.error,
+            Error::SomethingWentWrong(something_went_wrong) => &something_went_wrong.error,
+        }
+    }
+
+    fn message_mut(&mut self) -> &mut String {
+        match self {
+            Error::FieldValidationError(FieldValidationErrors { error, .. })
+            | Error::AuthenticationFailure(AuthenticationError { error, .. })
+            | Error::NotFound(NotFoundError { error, .. })
+            | Error::Forbidden(ForbiddenError { error, .. })
+            | Error::Conflict(ConflictError { error, .. })
+            | Error::Gone(GoneError { error, .. })
+            | Error::UnprocessableEntity(UnprocessableEntityError { error, .. })
+            | Error::TooManyRequests(TooManyRequestsError { error, .. })
+            | Error::ServiceUnavailable(ServiceUnavailableError { error, .. })
+            | Error::PayloadTooLarge(PayloadTooLargeError { error, .. })
+            | Error::RequestTimeout(RequestTimeoutError { error, .. }) => error,
+            Error::BadRequestError(bad_request_error) => &mut bad_request_error.error,
+            Error::SomethingWentWrong(something_went_wrong) => &mut something_went_wrong.error,
         }
     }
 }
 
-impl From<ValidationErrors> for Error {
-    fn from(err: ValidationErrors) -> Self {
-        let mut fields: HashMap<String, String> = HashMap::new();
-        for error in err.field_errors() {
-            let field = error.0.to_string();
-            let errors: Vec<String> = error.1.iter().map(|x| x.to_string()).collect();
-            if field == "__all__" {
-                return Error::BadRequestError(BadRequestError::new(errors.join(" ")));
+fn with_optional_detail(problem_details: ProblemDetails, detail: &str) -> ProblemDetails {
+    if detail.is_empty() {
+        return problem_details;
+    }
+    problem_details.with_detail(detail)
+}
+
+///The message of the error, or its details for 500s and authentication failures.
+///Falls back to the error code when the message is empty.
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let message = match self {
+            Error::FieldValidationError(FieldValidationErrors { error, .. })
+            | Error::NotFound(NotFoundError { error, .. })
+            | Error::Forbidden(ForbiddenError { error, .. })
+            | Error::Conflict(ConflictError { error, .. })
+            | Error::Gone(GoneError { error, .. })
+            | Error::UnprocessableEntity(UnprocessableEntityError { error, .. })
+            | Error::TooManyRequests(TooManyRequestsError { error, .. })
+            | Error::ServiceUnavailable(ServiceUnavailableError { error, .. })
+            | Error::PayloadTooLarge(PayloadTooLargeError { error, .. })
+            | Error::RequestTimeout(RequestTimeoutError { error, .. }) => error,
+            Error::AuthenticationFailure(authentication_error) => {
+                &authentication_error.error_details
             }
-            fields.insert(field, errors.join(" "));
+            Error::BadRequestError(bad_request_error) => &bad_request_error.error,
+            Error::SomethingWentWrong(something_went_wrong) => &something_went_wrong.error_details,
+        };
+        match message.is_empty() {
+            true => write!(f, "{}", self.code()),
+            false => write!(f, "{message}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::SomethingWentWrong(something_went_wrong) => something_went_wrong
+                .source
+                .as_deref()
+                .map(|x| x as &(dyn std::error::Error + 'static)),
+            _ => None,
         }
-        Error::FieldValidationError(FieldValidationErrors { fields })
+    }
+}
+
+impl IntoResponse for Error {
+    ///The error is also added to the response extensions, so layers can render and report it.
+    ///`with_web_core` reports 5xx errors to the registered `ErrorReporter`s, outside of it
+    ///`SomethingWentWrong` is printed to stderr.
+    fn into_response(self) -> Response {
+        if let Error::SomethingWentWrong(something_went_wrong) = &self
+            && !is_reporting()
+        {
+            something_went_wrong.print_error();
+        }
+        let mut response = self.to_json_response();
+        response.extensions_mut().insert(self);
+        response
+    }
+}
+
+impl From<ValidationErrors> for Error {
+    fn from(err: ValidationErrors) -> Self {
+        Error::FieldValidationError(FieldValidationErrors::from(&err))
     }
 }
 
 impl From<ValidationError> for Error {
+    ///The code of the error is used as the field name, `__all__` is reported as a schema error.
     fn from(err: ValidationError) -> Self {
-        let mut fields: HashMap<String, String> = HashMap::new();
+        let field_error = FieldError::from(&err);
         if err.code == "__all__" {
-            return Error::BadRequestError(BadRequestError::new(err.message.unwrap().to_string()));
+            return Error::FieldValidationError(FieldValidationErrors::new(
+                HashMap::new(),
+                vec![field_error],
+            ));
+        }
+        let mut fields: HashMap<String, Vec<FieldError>> = HashMap::new();
+        fields.insert(err.code.to_string(), vec![field_error]);
+        Error::FieldValidationError(FieldValidationErrors::new(fields, vec![]))
+    }
+}
+
+impl From<JsonRejection> for Error {
+    fn from(rejection: JsonRejection) -> Self {
+        Error::from_rejection(
+            rejection.status(),
+            rejection.body_text(),
+            CoreErrorCode::InvalidJson,
+        )
+    }
+}
+
+impl From<PathRejection> for Error {
+    fn from(rejection: PathRejection) -> Self {
+        Error::from_rejection(
+            rejection.status(),
+            rejection.body_text(),
+            CoreErrorCode::InvalidPath,
+        )
+    }
+}
+
+impl From<QueryRejection> for Error {
+    fn from(rejection: QueryRejection) -> Self {
+        Error::from_rejection(
+            rejection.status(),
+            rejection.body_text(),
+            CoreErrorCode::InvalidQuery,
+        )
+    }
+}
+
+impl From<TypedHeaderRejection> for Error {
+    fn from(rejection: TypedHeaderRejection) -> Self {
+        match (rejection.name() == AUTHORIZATION, rejection.reason()) {
+            (true, TypedHeaderRejectionReason::Missing) => {
+                Error::new_unauthorized(&rejection.to_string())
+                    .with_code(CoreErrorCode::MissingToken)
+            }
+            (true, _) => Error::new_unauthorized(&rejection.to_string())
+                .with_code(CoreErrorCode::TokenInvalid),
+            (false, TypedHeaderRejectionReason::Missing) => {
+                Error::bad_request_error(&format!("Header `{}` is missing.", rejection.name()))
+                    .with_code(CoreErrorCode::MissingHeader)
+            }
+            (false, _) => {
+                Error::bad_request_error(&format!("Header `{}` is invalid.", rejection.name()))
+                    .with_code(CoreErrorCode::InvalidHeader)
+            }
         }
-        fields.insert(err.code.to_string(), err.message.unwrap().to_string());
-        Error::FieldValidationError(FieldValidationErrors { fields })
     }
 }
 
 impl From<askama::Error> for Error {
     fn from(value: askama::Error) -> Self {
-        Error::new_something_went_wrong(format!("Error while rendering template: {value:?}"))
+        Error::new_something_went_wrong_with_source("Error while rendering template", value)
+    }
+}
+
+///Malformed or unexpected json is 400 with code `request.invalid_json`, io failures are 500.
+///Map the errors of json the server produced itself to 500 explicitly.
+impl From<serde_json::Error> for Error {
+    fn from(value: serde_json::Error) -> Self {
+        use serde_json::error::Category;
+        match value.classify() {
+            Category::Syntax | Category::Data | Category::Eof => {
+                Error::bad_request_error(&format!("Invalid json : {value}"))
+                    .with_code(CoreErrorCode::InvalidJson)
+            }
+            Category::Io => {
+                Error::new_something_went_wrong_with_source("Error while processing json", value)
+            }
+        }
+    }
+}
+
+const SERIALIZATION_FAILURE_SQLSTATE: &str = "40001";
+
+///`RowNotFound` is 404, a unique violation is 409 naming the constraint, foreign key and check
+///violations are 422 and pool timeouts are 503. See `WebCoreOptions::with_constraint_message`.
+impl From<sqlx::Error> for Error {
+    fn from(value: sqlx::Error) -> Self {
+        match &value {
+            sqlx::Error::RowNotFound => Error::new_not_found(""),
+            sqlx::Error::Database(database_error) => match database_error.kind() {
+                sqlx::error::ErrorKind::UniqueViolation => {
+                    constraints::unique_violation(database_error.constraint())
+                }
+                sqlx::error::ErrorKind::ForeignKeyViolation => {
+                    constraints::foreign_key_violation(database_error.constraint())
+                }
+                sqlx::error::ErrorKind::CheckViolation => {
+                    constraints::check_violation(database_error.constraint())
+                }
+                _ if database_error.code().as_deref() == Some(SERIALIZATION_FAILURE_SQLSTATE) => {
+                    constraints::serialization_failure()
+                }
+                _ => {
+                    Error::new_something_went_wrong_with_source("Error while running query", value)
+                }
+            },
+            sqlx::Error::PoolTimedOut => Error::new_service_unavailable("", None),
+            _ => Error::new_something_went_wrong_with_source("Error while running query", value),
+        }
+    }
+}
+
+///Timeouts are 503 with code `upstream.timeout`, every other failure is treated as a bug.
+impl From<reqwest::Error> for Error {
+    fn from(value: reqwest::Error) -> Self {
+        if value.is_timeout() {
+            return Error::new_service_unavailable("", None)
+                .with_code(CoreErrorCode::UpstreamTimeout);
+        }
+        Error::new_something_went_wrong_with_source("Error while sending request", value)
+    }
+}
+
+///Problems with the token are 401, problems with the keys or algorithms are 500.
+impl From<jsonwebtoken::errors::Error> for Error {
+    fn from(value: jsonwebtoken::errors::Error) -> Self {
+        use jsonwebtoken::errors::ErrorKind;
+        match value.kind() {
+            ErrorKind::ExpiredSignature => {
+                Error::new_unauthorized(&format!("Error while decoding token : {value}"))
+                    .with_code(CoreErrorCode::TokenExpired)
+            }
+            ErrorKind::InvalidEcdsaKey
+            | ErrorKind::InvalidRsaKey(_)
+            | ErrorKind::RsaFailedSigning
+            | ErrorKind::InvalidAlgorithmName
+            | ErrorKind::InvalidKeyFormat
+            | ErrorKind::MissingAlgorithm
+            | ErrorKind::Crypto(_) => {
+                Error::new_something_went_wrong_with_source("Error while handling token", value)
+            }
+            _ => Error::new_unauthorized(&format!("Error while decoding token : {value}"))
+                .with_code(CoreErrorCode::TokenInvalid),
+        }
     }
 }
 
 #[cfg(feature = "diesel")]
 impl From<diesel::r2d2::PoolError> for Error {
     fn from(value: diesel::r2d2::PoolError) -> Self {
-        Error::new_something_went_wrong(format!("{} {:?}", value.to_string(), value))
+        Error::new_something_went_wrong_with_source(
+            "Error while getting connection from pool",
+            value,
+        )
     }
 }
 
+///`NotFound` is 404, a unique violation is 409 naming the constraint, foreign key and check
+///violations are 422 and serialization failures are a retryable 503.
+///See `WebCoreOptions::with_constraint_message`.
 #[cfg(feature = "diesel")]
 impl From<diesel::result::Error> for Error {
     fn from(value: diesel::result::Error) -> Self {
-        Error::new_something_went_wrong(format!("{} {:?}", value.to_string(), value))
+        use diesel::result::DatabaseErrorKind;
+        match &value {
+            diesel::result::Error::NotFound => Error::new_not_found(""),
+            diesel::result::Error::DatabaseError(kind, info) => match kind {
+                DatabaseErrorKind::UniqueViolation => {
+                    constraints::unique_violation(info.constraint_name())
+                }
+                DatabaseErrorKind::ForeignKeyViolation => {
+                    constraints::foreign_key_violation(info.constraint_name())
+                }
+                DatabaseErrorKind::CheckViolation => {
+                    constraints::check_violation(info.constraint_name())
+                }
+                DatabaseErrorKind::SerializationFailure => constraints::serialization_failure(),
+                _ => {
+                    Error::new_something_went_wrong_with_source("Error while running query", value)
+                }
+            },
+            _ => Error::new_something_went_wrong_with_source("Error while running query", value),
+        }
     }
 }
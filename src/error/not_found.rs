@@ -1,22 +1,26 @@
-use axum::{response::IntoResponse, Json};
+use axum::{Json, response::IntoResponse};
 use http::StatusCode;
 
-#[derive(Debug, serde::Serialize, serde::Deserialize)]
+use super::code::CoreErrorCode;
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct NotFoundError {
-    pub error: String
+    pub error: String,
+    #[serde(default)]
+    pub code: String,
 }
 
 impl IntoResponse for NotFoundError {
     fn into_response(self) -> axum::response::Response {
-        (
-            StatusCode::NOT_FOUND,
-            Json(serde_json::json!(self))
-        ).into_response()
+        (StatusCode::NOT_FOUND, Json(serde_json::json!(self))).into_response()
     }
 }
 
 impl NotFoundError {
     pub fn new(error: String) -> Self {
-        Self { error }
+        Self {
+            error,
+            code: CoreErrorCode::NotFound.to_string(),
+        }
     }
-}
\ No newline at end of file
+}
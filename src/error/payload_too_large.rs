@@ -0,0 +1,26 @@
+use axum::{Json, response::IntoResponse};
+use http::StatusCode;
+
+use super::code::CoreErrorCode;
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct PayloadTooLargeError {
+    pub error: String,
+    #[serde(default)]
+    pub code: String,
+}
+
+impl IntoResponse for PayloadTooLargeError {
+    fn into_response(self) -> axum::response::Response {
+        (StatusCode::PAYLOAD_TOO_LARGE, Json(serde_json::json!(self))).into_response()
+    }
+}
+
+impl PayloadTooLargeError {
+    pub fn new(error: String) -> Self {
+        Self {
+            error,
+            code: CoreErrorCode::PayloadTooLarge.to_string(),
+        }
+    }
+}
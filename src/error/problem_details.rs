@@ -0,0 +1,75 @@
+use axum::{
+    body::Body,
+    response::{IntoResponse, Response},
+};
+use http::{HeaderValue, StatusCode, header::CONTENT_TYPE};
+use serde_json::{Map, Value};
+
+pub const PROBLEM_JSON_CONTENT_TYPE: &str = "application/problem+json";
+
+///RFC 9457 problem details object.
+///Members other than the standard ones are kept in `extensions` and flattened into the json.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ProblemDetails {
+    #[serde(rename = "type", default = "ProblemDetails::default_type")]
+    pub problem_type: String,
+    pub title: String,
+    pub status: u16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instance: Option<String>,
+    #[serde(flatten)]
+    pub extensions: Map<String, Value>,
+}
+
+impl ProblemDetails {
+    pub fn new(status: StatusCode) -> Self {
+        Self {
+            problem_type: Self::default_type(),
+            title: status.canonical_reason().unwrap_or_default().to_string(),
+            status: status.as_u16(),
+            detail: None,
+            instance: None,
+            extensions: Map::new(),
+        }
+    }
+
+    pub fn with_detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+
+    pub fn with_instance(mut self, instance: impl Into<String>) -> Self {
+        self.instance = Some(instance.into());
+        self
+    }
+
+    pub fn with_extension(mut self, key: &str, value: impl Into<Value>) -> Self {
+        self.extensions.insert(key.to_string(), value.into());
+        self
+    }
+
+    pub fn status_code(&self) -> StatusCode {
+        StatusCode::from_u16(self.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR)
+    }
+
+    fn default_type() -> String {
+        String::from("about:blank")
+    }
+}
+
+impl IntoResponse for ProblemDetails {
+    fn into_response(self) -> Response {
+        let body = serde_json::to_vec(&self).unwrap_or_default();
+        (
+            self.status_code(),
+            [(
+                CONTENT_TYPE,
+                HeaderValue::from_static(PROBLEM_JSON_CONTENT_TYPE),
+            )],
+            Body::from(body),
+        )
+            .into_response()
+    }
+}
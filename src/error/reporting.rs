@@ -0,0 +1,163 @@
+use std::{
+    fmt::Debug,
+    fs::{File, OpenOptions},
+    io::Write,
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use chrono::{DateTime, Utc};
+
+use super::something_went_wrong::SomethingWentWrong;
+
+///Details of a 5xx response sent to the registered `ErrorReporter`s.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ErrorReport {
+    pub timestamp: DateTime<Utc>,
+    pub status: u16,
+    pub code: Option<String>,
+    pub error_id: Option<String>,
+    pub details: Option<String>,
+    pub method: String,
+    pub path: String,
+    pub subject: Option<String>,
+    pub request_id: Option<String>,
+    ///Display of the source error and each of its `source()`s.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub source_chain: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backtrace: Option<String>,
+}
+
+///Report of an error raised outside of a request handled by `with_web_core`, so without
+///request details.
+impl From<&SomethingWentWrong> for ErrorReport {
+    fn from(something_went_wrong: &SomethingWentWrong) -> Self {
+        Self {
+            timestamp: Utc::now(),
+            status: 500,
+            code: Some(something_went_wrong.code.clone()),
+            error_id: Some(something_went_wrong.error_id.clone()),
+            details: Some(something_went_wrong.error_details.clone()),
+            method: String::new(),
+            path: String::new(),
+            subject: None,
+            request_id: something_went_wrong.request_id.clone(),
+            source_chain: something_went_wrong.source_chain(),
+            backtrace: something_went_wrong.captured_backtrace(),
+        }
+    }
+}
+
+///Receives every 5xx response rendered by `with_web_core`.
+///`report` is called on the request task, so implementations which do io over the network
+///should hand the report off (eg: to a channel or a spawned task) instead of blocking.
+pub trait ErrorReporter: Send + Sync {
+    fn report(&self, report: &ErrorReport);
+}
+
+///Prints the error id and details to stderr. Used when no reporter is registered.
+#[derive(Debug, Clone, Default)]
+pub struct ConsoleReporter;
+
+impl ErrorReporter for ConsoleReporter {
+    fn report(&self, report: &ErrorReport) {
+        if let Some(error_id) = &report.error_id {
+            eprintln!("Error: {}", error_id);
+        }
+        if let Some(request_id) = &report.request_id {
+            eprintln!("Request: {}", request_id);
+        }
+        let request = match report.method.is_empty() {
+            true => String::new(),
+            false => format!(" {} {}", report.method, report.path),
+        };
+        eprintln!(
+            "Something went wrong : [{}]{request} {}",
+            report.status,
+            report.details.as_deref().unwrap_or_default()
+        );
+        for (index, source) in report.source_chain.iter().enumerate() {
+            eprintln!("  {index}: {source}");
+        }
+        if let Some(backtrace) = &report.backtrace {
+            eprintln!("Backtrace:\n{backtrace}");
+        }
+    }
+}
+
+///Prints every report as a single json line to stdout.
+#[derive(Debug, Clone, Default)]
+pub struct StdoutJsonReporter;
+
+impl ErrorReporter for StdoutJsonReporter {
+    fn report(&self, report: &ErrorReport) {
+        match serde_json::to_string(report) {
+            Ok(json) => println!("{json}"),
+            Err(e) => eprintln!("Error while converting error report to json : {e}"),
+        }
+    }
+}
+
+///Appends every report as a json line to a file.
+#[derive(Debug)]
+pub struct FileReporter {
+    file: Mutex<File>,
+}
+
+impl FileReporter {
+    pub fn new(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self {
+            file: Mutex::new(file),
+        })
+    }
+}
+
+impl ErrorReporter for FileReporter {
+    fn report(&self, report: &ErrorReport) {
+        let Ok(json) = serde_json::to_string(report) else {
+            return;
+        };
+        let mut file = self.file.lock().unwrap_or_else(|e| e.into_inner());
+        if let Err(e) = writeln!(file, "{json}") {
+            eprintln!("Error while writing error report to file : {e}");
+        }
+    }
+}
+
+///Keeps the reports in memory so tests can assert on them.
+///Clones share the same reports.
+#[derive(Debug, Clone, Default)]
+pub struct InMemoryReporter {
+    reports: Arc<Mutex<Vec<ErrorReport>>>,
+}
+
+impl InMemoryReporter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn reports(&self) -> Vec<ErrorReport> {
+        self.reports
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    pub fn clear(&self) {
+        self.reports
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clear();
+    }
+}
+
+impl ErrorReporter for InMemoryReporter {
+    fn report(&self, report: &ErrorReport) {
+        self.reports
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(report.clone());
+    }
+}
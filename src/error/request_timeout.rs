@@ -0,0 +1,26 @@
+use axum::{Json, response::IntoResponse};
+use http::StatusCode;
+
+use super::code::CoreErrorCode;
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct RequestTimeoutError {
+    pub error: String,
+    #[serde(default)]
+    pub code: String,
+}
+
+impl IntoResponse for RequestTimeoutError {
+    fn into_response(self) -> axum::response::Response {
+        (StatusCode::REQUEST_TIMEOUT, Json(serde_json::json!(self))).into_response()
+    }
+}
+
+impl RequestTimeoutError {
+    pub fn new(error: String) -> Self {
+        Self {
+            error,
+            code: CoreErrorCode::RequestTimeout.to_string(),
+        }
+    }
+}
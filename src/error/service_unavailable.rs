@@ -0,0 +1,42 @@
+use std::time::Duration;
+
+use axum::{Json, response::IntoResponse};
+use http::{StatusCode, header::RETRY_AFTER};
+
+use super::code::CoreErrorCode;
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ServiceUnavailableError {
+    pub error: String,
+    #[serde(default)]
+    pub code: String,
+    ///Seconds after which the client may retry. Also sent as the `Retry-After` header.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retry_after: Option<u64>,
+}
+
+impl IntoResponse for ServiceUnavailableError {
+    fn into_response(self) -> axum::response::Response {
+        let mut response = (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(serde_json::json!(self)),
+        )
+            .into_response();
+        if let Some(retry_after) = self.retry_after {
+            response
+                .headers_mut()
+                .insert(RETRY_AFTER, retry_after.into());
+        }
+        response
+    }
+}
+
+impl ServiceUnavailableError {
+    pub fn new(error: String, retry_after: Option<Duration>) -> Self {
+        Self {
+            error,
+            code: CoreErrorCode::ServiceUnavailable.to_string(),
+            retry_after: retry_after.map(|x| x.as_secs()),
+        }
+    }
+}
@@ -1,14 +1,43 @@
+use std::{backtrace::Backtrace, sync::Arc};
+
 use axum::{
     Json,
     response::{IntoResponse, Response},
 };
 use http::StatusCode;
 
+use super::{
+    code::CoreErrorCode,
+    reporting::{ConsoleReporter, ErrorReport, ErrorReporter},
+};
+use crate::middleware::request_id::current_request_id;
+
+pub const SOMETHING_WENT_WRONG_MESSAGE: &str = "Something went wrong";
+
 #[derive(Debug, Clone, serde::Deserialize)]
 pub struct SomethingWentWrong {
+    #[serde(default)]
+    pub error: String,
+
     pub error_id: String,
 
+    ///Id of the request being handled when the error was raised, sent by the client or
+    ///generated by `with_request_id_layer`.
+    #[serde(default)]
+    pub request_id: Option<String>,
+
     pub error_details: String,
+
+    #[serde(default)]
+    pub code: String,
+
+    ///Original error, kept for reporting. Never sent in the response.
+    #[serde(skip)]
+    pub source: Option<Arc<dyn std::error::Error + Send + Sync>>,
+
+    ///Captured when `RUST_BACKTRACE` or `RUST_LIB_BACKTRACE` is set. Never sent in the response.
+    #[serde(skip)]
+    pub backtrace: Option<Arc<Backtrace>>,
 }
 
 use serde::ser::SerializeStruct;
@@ -19,11 +48,16 @@ impl serde::Serialize for SomethingWentWrong {
     where
         S: serde::Serializer,
     {
-        let field_count = if cfg!(debug_assertions) { 3 } else { 2 };
+        let field_count = if cfg!(debug_assertions) { 5 } else { 4 };
         let mut state = serializer.serialize_struct("SomethingWentWrong", field_count)?;
 
-        state.serialize_field("error", "Something went wrong")?;
+        state.serialize_field("error", &self.error)?;
+        state.serialize_field("code", &self.code)?;
         state.serialize_field("error_id", &self.error_id)?;
+        match &self.request_id {
+            Some(request_id) => state.serialize_field("request_id", request_id)?,
+            None => state.skip_field("request_id")?,
+        }
         if cfg!(debug_assertions) {
             state.serialize_field("error_details", &self.error_details)?;
         }
@@ -33,19 +67,62 @@ impl serde::Serialize for SomethingWentWrong {
 }
 
 impl SomethingWentWrong {
+    ///Prints the error with `ConsoleReporter`.
     pub fn print_error(&self) {
-        eprintln!("Error: {}", self.error_id);
-        eprintln!("Something went wrong : {}", self.error_details);
+        ConsoleReporter.report(&ErrorReport::from(self));
+    }
+
+    ///Display of the source error followed by each of its `source()`s.
+    pub fn source_chain(&self) -> Vec<String> {
+        let mut chain = vec![];
+        let mut current: Option<&(dyn std::error::Error + 'static)> = match &self.source {
+            Some(source) => Some(source.as_ref()),
+            None => None,
+        };
+        while let Some(error) = current {
+            chain.push(error.to_string());
+            current = error.source();
+        }
+        chain
+    }
+
+    pub fn captured_backtrace(&self) -> Option<String> {
+        self.backtrace.as_ref().map(|x| x.to_string())
     }
 }
 
 impl SomethingWentWrong {
     pub fn new(error_details: impl std::fmt::Debug) -> Self {
         Self {
+            error: SOMETHING_WENT_WRONG_MESSAGE.to_string(),
             error_id: format!("Error-{}", uuid::Uuid::new_v4()),
+            request_id: current_request_id().map(|x| x.to_string()),
             error_details: format!("{:?}", error_details),
+            code: CoreErrorCode::InternalError.to_string(),
+            source: None,
+            backtrace: capture_backtrace(),
         }
     }
+
+    ///Keeps `source` and its chain for reporting. `context` describes what was being done.
+    pub fn with_source(
+        context: impl std::fmt::Display,
+        source: impl Into<Box<dyn std::error::Error + Send + Sync>>,
+    ) -> Self {
+        let source: Arc<dyn std::error::Error + Send + Sync> = Arc::from(source.into());
+        let mut something_went_wrong = Self::new(());
+        something_went_wrong.error_details = format!("{context} : {source}");
+        something_went_wrong.source = Some(source);
+        something_went_wrong
+    }
+}
+
+fn capture_backtrace() -> Option<Arc<Backtrace>> {
+    let backtrace = Backtrace::capture();
+    match backtrace.status() {
+        std::backtrace::BacktraceStatus::Captured => Some(Arc::new(backtrace)),
+        _ => None,
+    }
 }
 
 impl IntoResponse for SomethingWentWrong {
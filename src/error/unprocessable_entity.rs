@@ -0,0 +1,35 @@
+use axum::{Json, response::IntoResponse};
+use http::StatusCode;
+
+use super::code::CoreErrorCode;
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct UnprocessableEntityError {
+    pub error: String,
+    #[serde(default)]
+    pub code: String,
+    ///Database constraint which was violated, its message can be replaced with
+    ///`WebCoreOptions::with_constraint_message`. Never sent in the response.
+    #[serde(skip)]
+    pub constraint: Option<String>,
+}
+
+impl IntoResponse for UnprocessableEntityError {
+    fn into_response(self) -> axum::response::Response {
+        (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(serde_json::json!(self)),
+        )
+            .into_response()
+    }
+}
+
+impl UnprocessableEntityError {
+    pub fn new(error: String) -> Self {
+        Self {
+            error,
+            code: CoreErrorCode::UnprocessableEntity.to_string(),
+            constraint: None,
+        }
+    }
+}
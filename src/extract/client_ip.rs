@@ -0,0 +1,42 @@
+use std::{fmt::Display, net::IpAddr};
+
+use axum::{
+    extract::{FromRequestParts, OptionalFromRequestParts},
+    http::request::Parts,
+};
+
+use crate::error::Error;
+
+///Address of the caller, resolved by the client ip layer of `with_web_core`
+///using `ClientIpOptions`. Use `Option<ClientIp>` when the address may be unknown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClientIp(pub IpAddr);
+
+impl<S: Send + Sync> FromRequestParts<S> for ClientIp {
+    type Rejection = Error;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts.extensions.get::<ClientIp>().copied().ok_or_else(|| {
+            Error::new_something_went_wrong(
+                "Client ip is unknown. Serve with `into_make_service_with_connect_info`.".into(),
+            )
+        })
+    }
+}
+
+impl<S: Send + Sync> OptionalFromRequestParts<S> for ClientIp {
+    type Rejection = Error;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        _state: &S,
+    ) -> Result<Option<Self>, Self::Rejection> {
+        Ok(parts.extensions.get::<ClientIp>().copied())
+    }
+}
+
+impl Display for ClientIp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
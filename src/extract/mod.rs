@@ -0,0 +1,9 @@
+mod client_ip;
+mod json;
+mod path;
+mod query;
+
+pub use client_ip::ClientIp;
+pub use json::Json;
+pub use path::Path;
+pub use query::Query;
@@ -0,0 +1,43 @@
+#![cfg(feature = "diesel")]
+
+use std::time::Duration;
+
+use ::diesel::{
+    PgConnection, RunQueryDsl,
+    r2d2::{ConnectionManager, Pool},
+    sql_query,
+};
+use async_trait::async_trait;
+
+use super::{HealthCheck, HealthCheckError};
+
+///Runs `SELECT 1` on a connection from the pool.
+#[derive(Clone)]
+pub struct DieselHealthCheck {
+    pool: Pool<ConnectionManager<PgConnection>>,
+}
+
+impl DieselHealthCheck {
+    pub fn new(pool: Pool<ConnectionManager<PgConnection>>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl HealthCheck for DieselHealthCheck {
+    fn name(&self) -> &str {
+        "database"
+    }
+
+    async fn check(&self, timeout: Duration) -> Result<(), HealthCheckError> {
+        let pool = self.pool.clone();
+        tokio::task::spawn_blocking(move || {
+            //`get` would wait for the pool's own timeout, 30s by default, keeping the blocking
+            //thread busy long after the probe gave up.
+            let mut conn = pool.get_timeout(timeout)?;
+            sql_query("SELECT 1").execute(&mut conn)?;
+            Ok(())
+        })
+        .await?
+    }
+}
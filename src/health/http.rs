@@ -0,0 +1,99 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use reqwest::Client;
+use url::Url;
+
+use super::{HealthCheck, HealthCheckError};
+
+///Sends `GET url` to an outbound dependency and expects a 2xx response.
+#[derive(Clone)]
+pub struct HttpHealthCheck {
+    name: String,
+    url: Url,
+    client: Client,
+}
+
+impl HttpHealthCheck {
+    pub fn new(name: impl Into<String>, url: Url) -> Self {
+        Self {
+            name: name.into(),
+            url,
+            client: Client::new(),
+        }
+    }
+
+    pub fn with_client(mut self, client: Client) -> Self {
+        self.client = client;
+        self
+    }
+}
+
+#[async_trait]
+impl HealthCheck for HttpHealthCheck {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn check(&self, timeout: Duration) -> Result<(), HealthCheckError> {
+        self.client
+            .get(self.url.clone())
+            .timeout(timeout)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+}
+
+///Fetches a JWKS endpoint and expects at least one key, eg:
+///`https://www.googleapis.com/oauth2/v3/certs` for google sign in.
+///
+///web-core does not cache JWKS itself, so this checks the endpoint the keys are fetched from.
+#[derive(Clone)]
+pub struct JwksHealthCheck {
+    url: Url,
+    client: Client,
+}
+
+impl JwksHealthCheck {
+    pub fn new(url: Url) -> Self {
+        Self {
+            url,
+            client: Client::new(),
+        }
+    }
+
+    pub fn with_client(mut self, client: Client) -> Self {
+        self.client = client;
+        self
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct Jwks {
+    keys: Vec<serde_json::Value>,
+}
+
+#[async_trait]
+impl HealthCheck for JwksHealthCheck {
+    fn name(&self) -> &str {
+        "jwks"
+    }
+
+    async fn check(&self, timeout: Duration) -> Result<(), HealthCheckError> {
+        let jwks: Jwks = self
+            .client
+            .get(self.url.clone())
+            .timeout(timeout)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        if jwks.keys.is_empty() {
+            return Err("JWKS has no keys".into());
+        }
+        Ok(())
+    }
+}
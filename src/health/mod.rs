@@ -0,0 +1,191 @@
+pub mod diesel;
+pub mod http;
+
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use axum::{
+    Json, Router,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::get,
+};
+use futures::future::join_all;
+use tokio::sync::Mutex;
+
+use crate::web_core::WebCoreState;
+
+#[cfg(feature = "diesel")]
+pub use diesel::DieselHealthCheck;
+pub use http::{HttpHealthCheck, JwksHealthCheck};
+
+pub const LIVE_PATH: &str = "/health/live";
+pub const READY_PATH: &str = "/health/ready";
+
+pub type HealthCheckError = Box<dyn std::error::Error + Send + Sync>;
+
+///A dependency the instance needs to serve requests, eg: the database.
+#[async_trait]
+pub trait HealthCheck: Send + Sync {
+    ///Shown in the readiness response.
+    fn name(&self) -> &str;
+
+    ///`timeout` is the time the check gets. The check is dropped once it runs out, so blocking
+    ///work, which dropping does not stop, should give up by then on its own.
+    async fn check(&self, timeout: Duration) -> Result<(), HealthCheckError>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthStatus {
+    Ok,
+    Unavailable,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct CheckResult {
+    pub name: String,
+    pub status: HealthStatus,
+    pub latency_ms: u64,
+    ///Only sent when `HealthOptions::with_error_details` is used, failures are logged either way.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct HealthReport {
+    pub status: HealthStatus,
+    pub checks: Vec<CheckResult>,
+}
+
+impl IntoResponse for HealthReport {
+    fn into_response(self) -> Response {
+        let status = match self.status {
+            HealthStatus::Ok => StatusCode::OK,
+            HealthStatus::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+        };
+        (status, Json(self)).into_response()
+    }
+}
+
+///Mounts `/health/live`, which answers as long as the process serves requests, and
+///`/health/ready`, which answers 503 when any check fails or times out.
+#[derive(Clone)]
+pub struct HealthOptions {
+    pub checks: Vec<Arc<dyn HealthCheck>>,
+    ///Time each check gets before it is reported as unavailable. Defaults to 2 seconds.
+    pub timeout: Duration,
+    ///Sends the error of failed checks in the readiness response. The routes are not
+    ///authenticated, so errors are only logged by default.
+    pub error_details: bool,
+    ///How long a readiness report is reused, so that probes can not hammer the dependencies.
+    ///Defaults to 1 second.
+    pub cache_ttl: Duration,
+    cached: Arc<Mutex<Option<(Instant, HealthReport)>>>,
+}
+
+impl Default for HealthOptions {
+    fn default() -> Self {
+        Self {
+            checks: vec![],
+            timeout: Duration::from_secs(2),
+            error_details: false,
+            cache_ttl: Duration::from_secs(1),
+            cached: Arc::new(Mutex::new(None)),
+        }
+    }
+}
+
+impl HealthOptions {
+    pub fn with_check(mut self, check: impl HealthCheck + 'static) -> Self {
+        self.checks.push(Arc::new(check));
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn with_error_details(mut self) -> Self {
+        self.error_details = true;
+        self
+    }
+
+    pub fn with_cache_ttl(mut self, cache_ttl: Duration) -> Self {
+        self.cache_ttl = cache_ttl;
+        self
+    }
+
+    ///Report of the last `cache_ttl`, concurrent probes wait for the same run of the checks.
+    pub async fn cached_report(&self) -> HealthReport {
+        let mut cached = self.cached.lock().await;
+        if let Some((reported_at, report)) = cached.as_ref()
+            && reported_at.elapsed() < self.cache_ttl
+        {
+            return report.clone();
+        }
+        let report = self.report().await;
+        *cached = Some((Instant::now(), report.clone()));
+        report
+    }
+
+    ///Runs every check concurrently.
+    pub async fn report(&self) -> HealthReport {
+        let checks = join_all(self.checks.iter().map(|check| async move {
+            let started_at = Instant::now();
+            let result = tokio::time::timeout(self.timeout, check.check(self.timeout)).await;
+            let error = match result {
+                Ok(Ok(())) => None,
+                Ok(Err(e)) => Some(e.to_string()),
+                Err(_) => Some(format!("Timed out after {}ms", self.timeout.as_millis())),
+            };
+            if let Some(error) = &error {
+                tracing::warn!(check = check.name(), "Health check failed : {error}");
+            }
+            CheckResult {
+                name: check.name().to_string(),
+                status: match error {
+                    Some(_) => HealthStatus::Unavailable,
+                    None => HealthStatus::Ok,
+                },
+                latency_ms: started_at.elapsed().as_millis() as u64,
+                error: error.filter(|_| self.error_details),
+            }
+        }))
+        .await;
+        let status = match checks.iter().all(|x| x.status == HealthStatus::Ok) {
+            true => HealthStatus::Ok,
+            false => HealthStatus::Unavailable,
+        };
+        HealthReport { status, checks }
+    }
+}
+
+pub trait HealthRoutes {
+    fn with_health_routes(self, options: HealthOptions) -> Self;
+}
+
+impl<T: Clone + Send + Sync + 'static> HealthRoutes for Router<WebCoreState<T>> {
+    fn with_health_routes(self, options: HealthOptions) -> Self {
+        self.route(
+            LIVE_PATH,
+            get(|| async {
+                Json(HealthReport {
+                    status: HealthStatus::Ok,
+                    checks: vec![],
+                })
+            }),
+        )
+        .route(
+            READY_PATH,
+            get(move || {
+                let options = options.clone();
+                async move { options.cached_report().await }
+            }),
+        )
+    }
+}
@@ -1,9 +1,16 @@
 pub mod auth;
+pub mod config;
 pub mod cors;
 pub mod error;
+pub mod extract;
+pub mod health;
 pub mod macros;
+pub mod metrics;
 pub mod middleware;
+pub mod openapi;
 pub mod reqwest;
+pub mod route_group;
+pub mod serve;
 pub mod test;
 pub mod utils;
 pub mod validators;
@@ -14,6 +21,7 @@ pub mod aws;
 pub mod diesel;
 
 pub use serde_json;
+pub use serve::{ServeOptions, serve};
 
 #[cfg(feature = "diesel")]
 pub use web_core_derive::diesel_jsonb;
@@ -1,8 +1,16 @@
+///Every error macro accepts a leading `code = ...` argument which is passed to `Error::with_code`,
+///eg: `bad_request!(code = MyCode::OutOfStock, "{item} is out of stock")`.
 #[macro_export]
 macro_rules! something_went_wrong {
+    (code = $code:expr) => {
+        $crate::something_went_wrong!().with_code($code)
+    };
+    (code = $code:expr, $($arg:tt)*) => {{
+        $crate::something_went_wrong!($($arg)*).with_code($code)
+    }};
     () => {
-        $crate::error::Error::new_something_went_wrong("")
-    };    
+        $crate::error::Error::new_something_went_wrong(String::new())
+    };
     ($($arg:tt)*) => {{
         $crate::error::Error::new_something_went_wrong(format!($($arg)*))
     }};
@@ -13,6 +21,12 @@ macro_rules! something_went_wrong {
 
 #[macro_export]
 macro_rules! unauthorized {
+    (code = $code:expr) => {
+        $crate::unauthorized!().with_code($code)
+    };
+    (code = $code:expr, $($arg:tt)*) => {{
+        $crate::unauthorized!($($arg)*).with_code($code)
+    }};
     () => {
         $crate::error::Error::new_unauthorized("")
     };
@@ -23,18 +37,33 @@ macro_rules! unauthorized {
 
 #[macro_export]
 macro_rules! bad_request {
+    (code = $code:expr) => {
+        $crate::bad_request!().with_code($code)
+    };
+    (code = $code:expr, $($arg:tt)*) => {{
+        $crate::bad_request!($($arg)*).with_code($code)
+    }};
+    () => {
+        $crate::error::Error::bad_request_error("")
+    };
     ($a:expr, $b:tt) => {
-        $crate::error::Error::BadRequestError($crate::error::bad_request::BadRequestError::new_with_data(format!($a), $crate::serde_json::json!($b)))
+        $crate::error::Error::BadRequestError(::std::boxed::Box::new($crate::error::bad_request::BadRequestError::new_with_data(format!($a), $crate::serde_json::json!($b))))
     };
 
     ($($arg:tt)*) => {{
-        $crate::error::Error::BadRequestError($crate::error::bad_request::BadRequestError::new(format!($($arg)*)))
+        $crate::error::Error::BadRequestError(::std::boxed::Box::new($crate::error::bad_request::BadRequestError::new(format!($($arg)*))))
     }};
 
 }
 
 #[macro_export]
 macro_rules! not_found {
+    (code = $code:expr) => {
+        $crate::not_found!().with_code($code)
+    };
+    (code = $code:expr, $($arg:tt)*) => {{
+        $crate::not_found!($($arg)*).with_code($code)
+    }};
     () => {
         $crate::error::Error::new_not_found("")
     };
@@ -42,3 +71,145 @@ macro_rules! not_found {
         $crate::error::Error::new_not_found(&format!($($arg)*))
     }};
 }
+
+#[macro_export]
+macro_rules! forbidden {
+    (code = $code:expr) => {
+        $crate::forbidden!().with_code($code)
+    };
+    (code = $code:expr, $($arg:tt)*) => {{
+        $crate::forbidden!($($arg)*).with_code($code)
+    }};
+    () => {
+        $crate::error::Error::new_forbidden("")
+    };
+    ($($arg:tt)*) => {{
+        $crate::error::Error::new_forbidden(&format!($($arg)*))
+    }};
+}
+
+#[macro_export]
+macro_rules! conflict {
+    (code = $code:expr) => {
+        $crate::conflict!().with_code($code)
+    };
+    (code = $code:expr, $($arg:tt)*) => {{
+        $crate::conflict!($($arg)*).with_code($code)
+    }};
+    () => {
+        $crate::error::Error::new_conflict("")
+    };
+    ($($arg:tt)*) => {{
+        $crate::error::Error::new_conflict(&format!($($arg)*))
+    }};
+}
+
+#[macro_export]
+macro_rules! gone {
+    (code = $code:expr) => {
+        $crate::gone!().with_code($code)
+    };
+    (code = $code:expr, $($arg:tt)*) => {{
+        $crate::gone!($($arg)*).with_code($code)
+    }};
+    () => {
+        $crate::error::Error::new_gone("")
+    };
+    ($($arg:tt)*) => {{
+        $crate::error::Error::new_gone(&format!($($arg)*))
+    }};
+}
+
+#[macro_export]
+macro_rules! unprocessable_entity {
+    (code = $code:expr) => {
+        $crate::unprocessable_entity!().with_code($code)
+    };
+    (code = $code:expr, $($arg:tt)*) => {{
+        $crate::unprocessable_entity!($($arg)*).with_code($code)
+    }};
+    () => {
+        $crate::error::Error::new_unprocessable_entity("")
+    };
+    ($($arg:tt)*) => {{
+        $crate::error::Error::new_unprocessable_entity(&format!($($arg)*))
+    }};
+}
+
+#[macro_export]
+macro_rules! payload_too_large {
+    (code = $code:expr) => {
+        $crate::payload_too_large!().with_code($code)
+    };
+    (code = $code:expr, $($arg:tt)*) => {{
+        $crate::payload_too_large!($($arg)*).with_code($code)
+    }};
+    () => {
+        $crate::error::Error::new_payload_too_large("")
+    };
+    ($($arg:tt)*) => {{
+        $crate::error::Error::new_payload_too_large(&format!($($arg)*))
+    }};
+}
+
+#[macro_export]
+macro_rules! request_timeout {
+    (code = $code:expr) => {
+        $crate::request_timeout!().with_code($code)
+    };
+    (code = $code:expr, $($arg:tt)*) => {{
+        $crate::request_timeout!($($arg)*).with_code($code)
+    }};
+    () => {
+        $crate::error::Error::new_request_timeout("")
+    };
+    ($($arg:tt)*) => {{
+        $crate::error::Error::new_request_timeout(&format!($($arg)*))
+    }};
+}
+
+///`too_many_requests!(retry_after = Duration::from_secs(30), "message")` also sets the `Retry-After` header.
+#[macro_export]
+macro_rules! too_many_requests {
+    (code = $code:expr) => {
+        $crate::too_many_requests!().with_code($code)
+    };
+    (code = $code:expr, $($arg:tt)*) => {{
+        $crate::too_many_requests!($($arg)*).with_code($code)
+    }};
+    () => {
+        $crate::error::Error::new_too_many_requests("", None)
+    };
+    (retry_after = $retry_after:expr) => {
+        $crate::error::Error::new_too_many_requests("", Some($retry_after))
+    };
+    (retry_after = $retry_after:expr, $($arg:tt)*) => {{
+        $crate::error::Error::new_too_many_requests(&format!($($arg)*), Some($retry_after))
+    }};
+    ($($arg:tt)*) => {{
+        $crate::error::Error::new_too_many_requests(&format!($($arg)*), None)
+    }};
+}
+
+///`service_unavailable!(retry_after = Duration::from_secs(30), "message")` also sets the `Retry-After` header.
+#[macro_export]
+macro_rules! service_unavailable {
+    (code = $code:expr) => {
+        $crate::service_unavailable!().with_code($code)
+    };
+    (code = $code:expr, $($arg:tt)*) => {{
+        $crate::service_unavailable!($($arg)*).with_code($code)
+    }};
+    () => {
+        $crate::error::Error::new_service_unavailable("", None)
+    };
+    (retry_after = $retry_after:expr) => {
+        $crate::error::Error::new_service_unavailable("", Some($retry_after))
+    };
+    (retry_after = $retry_after:expr, $($arg:tt)*) => {{
+        $crate::error::Error::new_service_unavailable(&format!($($arg)*), Some($retry_after))
+    }};
+    ($($arg:tt)*) => {{
+        $crate::error::Error::new_service_unavailable(&format!($($arg)*), None)
+    }};
+}
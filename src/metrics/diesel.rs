@@ -0,0 +1,20 @@
+#![cfg(feature = "diesel")]
+
+use ::diesel::{
+    PgConnection,
+    r2d2::{ConnectionManager, Pool},
+};
+
+use super::{PoolStats, metrics};
+
+///Reports the pool as `db_pool_connections{pool="<name>"}`, sampled on every scrape.
+pub fn register_diesel_pool(name: impl Into<String>, pool: Pool<ConnectionManager<PgConnection>>) {
+    metrics().register_pool(name, move || {
+        let state = pool.state();
+        PoolStats {
+            connections: state.connections,
+            idle_connections: state.idle_connections,
+            max_size: pool.max_size(),
+        }
+    });
+}
@@ -0,0 +1,251 @@
+pub mod diesel;
+pub mod registry;
+
+use std::{
+    sync::{LazyLock, Mutex},
+    time::{Duration, Instant},
+};
+
+use axum::{
+    Router,
+    body::Body,
+    extract::{MatchedPath, Request},
+    http::{Method, StatusCode, header},
+    middleware::{self, Next},
+    response::IntoResponse,
+    routing::get,
+};
+
+use crate::web_core::WebCoreState;
+
+#[cfg(feature = "diesel")]
+pub use diesel::register_diesel_pool;
+pub use registry::{CounterVec, GaugeVec, HistogramData, HistogramVec};
+
+pub const METRICS_PATH: &str = "/metrics";
+pub const PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+///Route label of requests that matched no route, so unknown paths do not create new series.
+pub const UNMATCHED_ROUTE: &str = "unmatched";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolStats {
+    pub connections: u32,
+    pub idle_connections: u32,
+    pub max_size: u32,
+}
+
+type PoolSampler = Box<dyn Fn() -> PoolStats + Send + Sync>;
+
+///Metrics recorded by web-core. Use `metrics()` to get the instance the layers record into.
+pub struct MetricsRegistry {
+    pub http_requests: CounterVec,
+    pub http_errors: CounterVec,
+    pub http_duration: HistogramVec,
+    pub auth_failures: CounterVec,
+    pub outbound_duration: HistogramVec,
+    pub db_pool_connections: GaugeVec,
+    pools: Mutex<Vec<(String, PoolSampler)>>,
+}
+
+impl Default for MetricsRegistry {
+    fn default() -> Self {
+        Self {
+            http_requests: CounterVec::new(
+                "http_requests_total",
+                "Total HTTP requests.",
+                &["method", "route", "status_class"],
+            ),
+            http_errors: CounterVec::new(
+                "http_request_errors_total",
+                "HTTP requests answered with a 5xx status.",
+                &["method", "route", "status_class"],
+            ),
+            http_duration: HistogramVec::new(
+                "http_request_duration_seconds",
+                "HTTP request latency.",
+                &["method", "route", "status_class"],
+            ),
+            auth_failures: CounterVec::new(
+                "auth_failures_total",
+                "Rejected authentications by error code.",
+                &["reason"],
+            ),
+            outbound_duration: HistogramVec::new(
+                "outbound_request_duration_seconds",
+                "Latency of outbound api calls.",
+                &["host", "outcome"],
+            ),
+            db_pool_connections: GaugeVec::new(
+                "db_pool_connections",
+                "Database pool connections, sampled on scrape.",
+                &["pool", "state"],
+            ),
+            pools: Mutex::new(vec![]),
+        }
+    }
+}
+
+impl MetricsRegistry {
+    pub fn record_request(&self, method: &str, route: &str, status: StatusCode, latency: Duration) {
+        let status_class = status_class(status);
+        let labels = [method, route, status_class];
+        self.http_requests.inc(&labels);
+        if status.is_server_error() {
+            self.http_errors.inc(&labels);
+        }
+        self.http_duration.observe(&labels, latency);
+    }
+
+    ///`reason` is the error code, eg: `auth.token_expired`.
+    pub fn record_auth_failure(&self, reason: &str) {
+        self.auth_failures.inc(&[reason]);
+    }
+
+    pub fn record_outbound(&self, host: &str, success: bool, latency: Duration) {
+        let outcome = match success {
+            true => "success",
+            false => "error",
+        };
+        self.outbound_duration.observe(&[host, outcome], latency);
+    }
+
+    ///`sampler` is called on every scrape, see `register_diesel_pool`.
+    pub fn register_pool(
+        &self,
+        name: impl Into<String>,
+        sampler: impl Fn() -> PoolStats + Send + Sync + 'static,
+    ) {
+        let mut pools = self.pools.lock().unwrap_or_else(|e| e.into_inner());
+        pools.push((name.into(), Box::new(sampler)));
+    }
+
+    fn sample_pools(&self) {
+        let pools = self.pools.lock().unwrap_or_else(|e| e.into_inner());
+        for (name, sampler) in pools.iter() {
+            let stats = sampler();
+            let in_use = stats.connections.saturating_sub(stats.idle_connections);
+            let gauges = [
+                ("in_use", in_use),
+                ("idle", stats.idle_connections),
+                ("max", stats.max_size),
+            ];
+            for (state, value) in gauges {
+                self.db_pool_connections
+                    .set(&[name, state], f64::from(value));
+            }
+        }
+    }
+
+    ///Prometheus text exposition format.
+    pub fn render(&self) -> String {
+        self.sample_pools();
+        let mut out = String::new();
+        self.http_requests.render(&mut out);
+        self.http_errors.render(&mut out);
+        self.http_duration.render(&mut out);
+        self.auth_failures.render(&mut out);
+        self.outbound_duration.render(&mut out);
+        self.db_pool_connections.render(&mut out);
+        out
+    }
+}
+
+static METRICS: LazyLock<MetricsRegistry> = LazyLock::new(MetricsRegistry::default);
+
+pub fn metrics() -> &'static MetricsRegistry {
+    &METRICS
+}
+
+///`2xx`, `4xx`, ...
+pub fn status_class(status: StatusCode) -> &'static str {
+    match status.as_u16() {
+        100..=199 => "1xx",
+        200..=299 => "2xx",
+        300..=399 => "3xx",
+        400..=499 => "4xx",
+        _ => "5xx",
+    }
+}
+
+///Method label, methods outside of the standard ones are `other` so they can not create new series.
+pub fn method_label(method: &Method) -> &'static str {
+    match *method {
+        Method::GET => "GET",
+        Method::HEAD => "HEAD",
+        Method::POST => "POST",
+        Method::PUT => "PUT",
+        Method::PATCH => "PATCH",
+        Method::DELETE => "DELETE",
+        Method::OPTIONS => "OPTIONS",
+        Method::CONNECT => "CONNECT",
+        Method::TRACE => "TRACE",
+        _ => "other",
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MetricsOptions {
+    ///Defaults to `/metrics`.
+    pub path: String,
+}
+
+impl Default for MetricsOptions {
+    fn default() -> Self {
+        Self {
+            path: METRICS_PATH.to_string(),
+        }
+    }
+}
+
+impl MetricsOptions {
+    pub fn with_path(mut self, path: impl Into<String>) -> Self {
+        self.path = path.into();
+        self
+    }
+}
+
+pub async fn metrics_middleware(req: Request<Body>, next: Next) -> axum::response::Response {
+    let started_at = Instant::now();
+    let method = method_label(req.method());
+    let route = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|x| x.as_str().to_string());
+    let response = next.run(req).await;
+    metrics().record_request(
+        method,
+        route.as_deref().unwrap_or(UNMATCHED_ROUTE),
+        response.status(),
+        started_at.elapsed(),
+    );
+    response
+}
+
+pub trait MetricsLayer {
+    ///Records request count, 5xx count and latency, labelled by route template.
+    fn with_metrics_layer(self) -> Self;
+    ///Mounts the Prometheus endpoint, place it after the layers so scrapes are not recorded.
+    fn with_metrics_route(self, options: MetricsOptions) -> Self;
+}
+
+impl<T> MetricsLayer for Router<WebCoreState<T>>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    fn with_metrics_layer(self) -> Self {
+        self.layer(middleware::from_fn(metrics_middleware))
+    }
+
+    fn with_metrics_route(self, options: MetricsOptions) -> Self {
+        self.route(
+            &options.path,
+            get(|| async {
+                (
+                    [(header::CONTENT_TYPE, PROMETHEUS_CONTENT_TYPE)],
+                    metrics().render(),
+                )
+                    .into_response()
+            }),
+        )
+    }
+}
@@ -0,0 +1,187 @@
+use std::{collections::BTreeMap, fmt::Write, sync::Mutex, time::Duration};
+
+///Upper bounds in seconds, suited to api latencies.
+pub const DEFAULT_BUCKETS: [f64; 11] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn render_labels(names: &[&str], values: &[String], extra: Option<(&str, &str)>) -> String {
+    let labels = names
+        .iter()
+        .zip(values)
+        .map(|(name, value)| (*name, value.as_str()))
+        .chain(extra)
+        .map(|(name, value)| format!("{name}=\"{}\"", escape(value)))
+        .collect::<Vec<_>>();
+    match labels.is_empty() {
+        true => String::new(),
+        false => format!("{{{}}}", labels.join(",")),
+    }
+}
+
+fn render_header(out: &mut String, name: &str, help: &str, kind: &str) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} {kind}");
+}
+
+fn to_values(labels: &[&str]) -> Vec<String> {
+    labels.iter().map(|x| x.to_string()).collect()
+}
+
+pub struct CounterVec {
+    name: &'static str,
+    help: &'static str,
+    label_names: &'static [&'static str],
+    values: Mutex<BTreeMap<Vec<String>, u64>>,
+}
+
+impl CounterVec {
+    pub fn new(
+        name: &'static str,
+        help: &'static str,
+        label_names: &'static [&'static str],
+    ) -> Self {
+        Self {
+            name,
+            help,
+            label_names,
+            values: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    ///`labels` are in the order of the label names.
+    pub fn inc(&self, labels: &[&str]) {
+        let mut values = self.values.lock().unwrap_or_else(|e| e.into_inner());
+        *values.entry(to_values(labels)).or_default() += 1;
+    }
+
+    pub fn get(&self, labels: &[&str]) -> u64 {
+        let values = self.values.lock().unwrap_or_else(|e| e.into_inner());
+        values.get(&to_values(labels)).copied().unwrap_or_default()
+    }
+
+    pub fn render(&self, out: &mut String) {
+        render_header(out, self.name, self.help, "counter");
+        let values = self.values.lock().unwrap_or_else(|e| e.into_inner());
+        for (labels, value) in values.iter() {
+            let labels = render_labels(self.label_names, labels, None);
+            let _ = writeln!(out, "{}{labels} {value}", self.name);
+        }
+    }
+}
+
+pub struct GaugeVec {
+    name: &'static str,
+    help: &'static str,
+    label_names: &'static [&'static str],
+    values: Mutex<BTreeMap<Vec<String>, f64>>,
+}
+
+impl GaugeVec {
+    pub fn new(
+        name: &'static str,
+        help: &'static str,
+        label_names: &'static [&'static str],
+    ) -> Self {
+        Self {
+            name,
+            help,
+            label_names,
+            values: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    pub fn set(&self, labels: &[&str], value: f64) {
+        let mut values = self.values.lock().unwrap_or_else(|e| e.into_inner());
+        values.insert(to_values(labels), value);
+    }
+
+    pub fn get(&self, labels: &[&str]) -> Option<f64> {
+        let values = self.values.lock().unwrap_or_else(|e| e.into_inner());
+        values.get(&to_values(labels)).copied()
+    }
+
+    pub fn render(&self, out: &mut String) {
+        render_header(out, self.name, self.help, "gauge");
+        let values = self.values.lock().unwrap_or_else(|e| e.into_inner());
+        for (labels, value) in values.iter() {
+            let labels = render_labels(self.label_names, labels, None);
+            let _ = writeln!(out, "{}{labels} {value}", self.name);
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HistogramData {
+    ///Observations per bucket, not cumulative.
+    pub buckets: Vec<u64>,
+    pub sum: f64,
+    pub count: u64,
+}
+
+pub struct HistogramVec {
+    name: &'static str,
+    help: &'static str,
+    label_names: &'static [&'static str],
+    buckets: Vec<f64>,
+    values: Mutex<BTreeMap<Vec<String>, HistogramData>>,
+}
+
+impl HistogramVec {
+    pub fn new(
+        name: &'static str,
+        help: &'static str,
+        label_names: &'static [&'static str],
+    ) -> Self {
+        Self {
+            name,
+            help,
+            label_names,
+            buckets: DEFAULT_BUCKETS.to_vec(),
+            values: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    pub fn observe(&self, labels: &[&str], duration: Duration) {
+        let seconds = duration.as_secs_f64();
+        let mut values = self.values.lock().unwrap_or_else(|e| e.into_inner());
+        let data = values.entry(to_values(labels)).or_default();
+        data.buckets.resize(self.buckets.len(), 0);
+        if let Some(index) = self.buckets.iter().position(|x| seconds <= *x) {
+            data.buckets[index] += 1;
+        }
+        data.sum += seconds;
+        data.count += 1;
+    }
+
+    pub fn get(&self, labels: &[&str]) -> Option<HistogramData> {
+        let values = self.values.lock().unwrap_or_else(|e| e.into_inner());
+        values.get(&to_values(labels)).cloned()
+    }
+
+    pub fn render(&self, out: &mut String) {
+        render_header(out, self.name, self.help, "histogram");
+        let values = self.values.lock().unwrap_or_else(|e| e.into_inner());
+        for (labels, data) in values.iter() {
+            let mut cumulative = 0;
+            for (bound, count) in self.buckets.iter().zip(&data.buckets) {
+                cumulative += count;
+                let le = bound.to_string();
+                let labels = render_labels(self.label_names, labels, Some(("le", &le)));
+                let _ = writeln!(out, "{}_bucket{labels} {cumulative}", self.name);
+            }
+            let bucket_labels = render_labels(self.label_names, labels, Some(("le", "+Inf")));
+            let _ = writeln!(out, "{}_bucket{bucket_labels} {}", self.name, data.count);
+            let labels = render_labels(self.label_names, labels, None);
+            let _ = writeln!(out, "{}_sum{labels} {}", self.name, data.sum);
+            let _ = writeln!(out, "{}_count{labels} {}", self.name, data.count);
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use std::{
+    any::Any,
+    backtrace::{Backtrace, BacktraceStatus},
+    cell::RefCell,
+    panic::PanicHookInfo,
+    sync::{Arc, Once},
+};
+
+use axum::{
+    Router,
+    response::{IntoResponse, Response},
+};
+use tower_http::catch_panic::CatchPanicLayer as TowerCatchPanicLayer;
+
+use crate::{
+    error::{Error, something_went_wrong::SomethingWentWrong},
+    middleware::request_id::current_request_id,
+    web_core::WebCoreState,
+};
+
+///Where the last panic of the current thread happened, recorded by the panic hook.
+struct PanicLocation {
+    location: String,
+    backtrace: Option<Backtrace>,
+}
+
+thread_local! {
+    static LAST_PANIC: RefCell<Option<PanicLocation>> = const { RefCell::new(None) };
+}
+
+static INSTALL_PANIC_HOOK: Once = Once::new();
+
+///Keeps the previous hook, so panics are still printed as usual.
+fn install_panic_hook() {
+    INSTALL_PANIC_HOOK.call_once(|| {
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info: &PanicHookInfo<'_>| {
+            let location = info
+                .location()
+                .map(|x| format!("{}:{}:{}", x.file(), x.line(), x.column()))
+                .unwrap_or_else(|| String::from("unknown location"));
+            let backtrace = Backtrace::capture();
+            let backtrace = (backtrace.status() == BacktraceStatus::Captured).then_some(backtrace);
+            LAST_PANIC.with(|x| {
+                *x.borrow_mut() = Some(PanicLocation {
+                    location,
+                    backtrace,
+                })
+            });
+            previous(info);
+        }));
+    });
+}
+
+fn panic_message(panic: &(dyn Any + Send)) -> &str {
+    if let Some(message) = panic.downcast_ref::<&str>() {
+        message
+    } else if let Some(message) = panic.downcast_ref::<String>() {
+        message
+    } else {
+        "Box<dyn Any>"
+    }
+}
+
+///Turns a handler panic into the usual `SomethingWentWrong` response. `with_web_core` reports it
+///like any other 5xx error.
+pub fn panic_response(panic: Box<dyn Any + Send + 'static>) -> Response {
+    let message = panic_message(panic.as_ref());
+    let panic_location = LAST_PANIC.with(|x| x.borrow_mut().take());
+    let location = panic_location
+        .as_ref()
+        .map(|x| x.location.as_str())
+        .unwrap_or("unknown location");
+    tracing::error!(
+        request_id = current_request_id().map(|x| x.to_string()),
+        location,
+        message,
+        "handler panicked"
+    );
+
+    let mut something_went_wrong =
+        SomethingWentWrong::new(format!("Handler panicked at {location} : {message}"));
+    if let Some(backtrace) = panic_location.and_then(|x| x.backtrace) {
+        something_went_wrong.backtrace = Some(Arc::new(backtrace));
+    }
+    Error::SomethingWentWrong(Box::new(something_went_wrong)).into_response()
+}
+
+pub trait CatchPanicLayer {
+    fn with_catch_panic_layer(self) -> Self;
+}
+
+impl<T: Clone + Send + Sync + 'static> CatchPanicLayer for Router<WebCoreState<T>> {
+    fn with_catch_panic_layer(self) -> Self {
+        install_panic_hook();
+        self.layer(TowerCatchPanicLayer::custom(panic_response))
+    }
+}
@@ -0,0 +1,182 @@
+use std::net::{IpAddr, SocketAddr};
+
+use axum::{
+    Router,
+    body::Body,
+    extract::ConnectInfo,
+    http::{Extensions, HeaderMap, Request},
+    middleware::{self, Next},
+    response::Response,
+};
+use lambda_http::request::RequestContext;
+
+use crate::{extract::ClientIp, utils::ip_cidr::IpCidr, web_core::WebCoreState};
+
+const X_FORWARDED_FOR: &str = "x-forwarded-for";
+const FORWARDED: &str = "forwarded";
+const CLOUDFRONT_VIEWER_ADDRESS: &str = "cloudfront-viewer-address";
+
+///Header the address of the caller is read from when the peer is a trusted proxy.
+///
+///Only one header is read. Proxies append to their own header and pass the others through as the
+///client sent them, so reading any other header would let the client pick its address.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ForwardedHeader {
+    ///`X-Forwarded-For`, appended to by ALB, nginx and most proxies.
+    #[default]
+    XForwardedFor,
+    ///RFC 7239 `Forwarded`, only use it when every trusted proxy appends to it.
+    Forwarded,
+    ///`CloudFront-Viewer-Address`, read whatever the peer is. Only use it when the app can not be
+    ///reached without going through CloudFront, since anyone can send the header.
+    CloudFrontViewerAddress,
+}
+
+///How the address of the caller is worked out.
+///
+///The immediate peer is the api gateway source ip on lambda, or the socket address when served with
+///`into_make_service_with_connect_info::<SocketAddr>()`. `header` is only read when the peer is one
+///of `trusted_proxies`, and is walked right to left skipping trusted proxies.
+#[derive(Debug, Clone, Default)]
+pub struct ClientIpOptions {
+    pub trusted_proxies: Vec<IpCidr>,
+    pub header: ForwardedHeader,
+}
+
+impl ClientIpOptions {
+    pub fn with_trusted_proxy(mut self, proxy: IpCidr) -> Self {
+        self.trusted_proxies.push(proxy);
+        self
+    }
+
+    pub fn with_trusted_proxies(mut self, proxies: impl IntoIterator<Item = IpCidr>) -> Self {
+        self.trusted_proxies.extend(proxies);
+        self
+    }
+
+    ///Defaults to `ForwardedHeader::XForwardedFor`.
+    pub fn with_header(mut self, header: ForwardedHeader) -> Self {
+        self.header = header;
+        self
+    }
+
+    fn is_trusted(&self, ip: &IpAddr) -> bool {
+        self.trusted_proxies.iter().any(|x| x.contains(ip))
+    }
+
+    ///`None` when neither the peer nor a trusted header is known.
+    pub fn resolve(&self, headers: &HeaderMap, extensions: &Extensions) -> Option<IpAddr> {
+        if self.header == ForwardedHeader::CloudFrontViewerAddress
+            && let Some(ip) = cloudfront_viewer_address(headers)
+        {
+            return Some(ip);
+        }
+
+        let peer = lambda_source_ip(extensions).or_else(|| {
+            extensions
+                .get::<ConnectInfo<SocketAddr>>()
+                .map(|x| x.0.ip())
+        })?;
+        if !self.is_trusted(&peer) {
+            return Some(peer);
+        }
+
+        let chain = match self.header {
+            ForwardedHeader::XForwardedFor => header_values(headers, X_FORWARDED_FOR)
+                .flat_map(|x| x.split(','))
+                .map(parse_node)
+                .collect::<Vec<_>>(),
+            ForwardedHeader::Forwarded => header_values(headers, FORWARDED)
+                .flat_map(|x| x.split(','))
+                .map(forwarded_for)
+                .collect(),
+            ForwardedHeader::CloudFrontViewerAddress => Vec::new(),
+        };
+        self.walk(&chain).or(Some(peer))
+    }
+
+    ///Rightmost address which is not a trusted proxy. An unreadable hop stops the walk, since
+    ///anything left of it can not be trusted.
+    fn walk(&self, chain: &[Option<IpAddr>]) -> Option<IpAddr> {
+        let mut leftmost = None;
+        for hop in chain.iter().rev() {
+            let ip = (*hop)?;
+            if !self.is_trusted(&ip) {
+                return Some(ip);
+            }
+            leftmost = Some(ip);
+        }
+        leftmost
+    }
+}
+
+fn header_values<'a>(headers: &'a HeaderMap, name: &str) -> impl Iterator<Item = &'a str> {
+    headers
+        .get_all(name)
+        .into_iter()
+        .filter_map(|x| x.to_str().ok())
+}
+
+fn lambda_source_ip(extensions: &Extensions) -> Option<IpAddr> {
+    let source_ip = match extensions.get::<RequestContext>()? {
+        RequestContext::ApiGatewayV2(context) => context.http.source_ip.as_deref(),
+        RequestContext::ApiGatewayV1(context) => context.identity.source_ip.as_deref(),
+        RequestContext::WebSocket(context) => context.identity.source_ip.as_deref(),
+        RequestContext::Alb(_) => None,
+    };
+    source_ip.and_then(|x| x.parse().ok())
+}
+
+///`198.51.100.10:46532` or `2001:db8::1:46532`, the port is always present.
+fn cloudfront_viewer_address(headers: &HeaderMap) -> Option<IpAddr> {
+    let value = headers.get(CLOUDFRONT_VIEWER_ADDRESS)?.to_str().ok()?;
+    let (ip, _port) = value.trim().rsplit_once(':')?;
+    ip.parse().ok()
+}
+
+///The `for=` parameter of one `Forwarded` element, eg: `for="[2001:db8::1]:4711";proto=https`.
+fn forwarded_for(element: &str) -> Option<IpAddr> {
+    element
+        .split(';')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| key.trim().eq_ignore_ascii_case("for"))
+        .and_then(|(_, value)| parse_node(value))
+}
+
+///`1.2.3.4`, `1.2.3.4:80`, `2001:db8::1` or `[2001:db8::1]:80`, optionally quoted.
+fn parse_node(node: &str) -> Option<IpAddr> {
+    let node = node.trim().trim_matches('"');
+    if let Ok(ip) = node.parse::<IpAddr>() {
+        return Some(ip);
+    }
+    if let Some(rest) = node.strip_prefix('[') {
+        return rest.split_once(']')?.0.parse().ok();
+    }
+    node.parse::<SocketAddr>().ok().map(|x| x.ip())
+}
+
+pub async fn client_ip_middleware(
+    mut req: Request<Body>,
+    next: Next,
+    options: ClientIpOptions,
+) -> Response {
+    if let Some(ip) = options.resolve(req.headers(), req.extensions()) {
+        req.extensions_mut().insert(ClientIp(ip));
+    }
+    next.run(req).await
+}
+
+pub trait ClientIpLayer {
+    fn with_client_ip_layer(self, options: ClientIpOptions) -> Self;
+}
+
+impl<T: Clone + Send + Sync + 'static> ClientIpLayer for Router<WebCoreState<T>> {
+    fn with_client_ip_layer(self, options: ClientIpOptions) -> Self {
+        self.layer(middleware::from_fn(
+            move |req: Request<Body>, next: Next| {
+                let options = options.clone();
+                async move { client_ip_middleware(req, next, options).await }
+            },
+        ))
+    }
+}
@@ -0,0 +1,195 @@
+use std::{collections::HashMap, sync::Arc};
+
+use chrono::Utc;
+
+use axum::{
+    Router,
+    body::Body,
+    http::{
+        HeaderValue, Request,
+        header::{ACCEPT_LANGUAGE, CONTENT_LANGUAGE, CONTENT_LENGTH},
+    },
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+};
+
+use crate::{
+    auth::authenticated_user::AuthenticatedUser,
+    error::{
+        Error, ErrorFormat,
+        constraints::apply_constraint_message,
+        localization::MessageCatalog,
+        reporting::{ConsoleReporter, ErrorReport, ErrorReporter},
+    },
+    middleware::request_id::RequestId,
+    web_core::WebCoreState,
+};
+
+tokio::task_local! {
+    static REPORTING: ();
+}
+
+///Whether the current task is handling a request under `with_error_rendering_layer`, which
+///reports its 5xx responses.
+pub fn is_reporting() -> bool {
+    REPORTING.try_with(|_| ()).is_ok()
+}
+
+#[derive(Clone, Default)]
+pub struct ErrorRenderingOptions {
+    pub format: ErrorFormat,
+    ///Messages are translated only when a catalog is set.
+    pub catalog: Option<Arc<MessageCatalog>>,
+    ///5xx responses are reported to `ConsoleReporter` when this is empty.
+    pub reporters: Vec<Arc<dyn ErrorReporter>>,
+    ///Messages of database errors by constraint name, eg: `users_email_key`.
+    pub constraint_messages: Arc<HashMap<String, String>>,
+}
+
+impl ErrorRenderingOptions {
+    pub fn with_format(mut self, format: ErrorFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    pub fn with_catalog(mut self, catalog: MessageCatalog) -> Self {
+        self.catalog = Some(Arc::new(catalog));
+        self
+    }
+
+    pub fn with_reporter(mut self, reporter: impl ErrorReporter + 'static) -> Self {
+        self.reporters.push(Arc::new(reporter));
+        self
+    }
+
+    pub fn with_constraint_message(
+        mut self,
+        constraint: impl Into<String>,
+        message: impl Into<String>,
+    ) -> Self {
+        Arc::make_mut(&mut self.constraint_messages).insert(constraint.into(), message.into());
+        self
+    }
+
+    fn report(&self, report: ErrorReport) {
+        if self.reporters.is_empty() {
+            ConsoleReporter.report(&report);
+        }
+        for reporter in &self.reporters {
+            reporter.report(&report);
+        }
+    }
+}
+
+fn error_report(
+    method: String,
+    path: String,
+    request_id: Option<String>,
+    response: &Response,
+) -> ErrorReport {
+    let error = response.extensions().get::<Error>();
+    let (error_id, details, source_chain, backtrace) = match error {
+        Some(Error::SomethingWentWrong(something_went_wrong)) => (
+            Some(something_went_wrong.error_id.clone()),
+            Some(something_went_wrong.error_details.clone()),
+            something_went_wrong.source_chain(),
+            something_went_wrong.captured_backtrace(),
+        ),
+        Some(error) => (None, Some(error.message().to_string()), vec![], None),
+        None => (None, None, vec![], None),
+    };
+    ErrorReport {
+        timestamp: Utc::now(),
+        status: response.status().as_u16(),
+        code: error.map(|x| x.code().to_string()),
+        error_id,
+        details,
+        method,
+        path,
+        subject: response
+            .extensions()
+            .get::<AuthenticatedUser>()
+            .map(|x| x.subject.clone()),
+        request_id,
+        source_chain,
+        backtrace,
+    }
+}
+
+///Reports 5xx responses and re-renders `Error` responses produced further down the stack
+///according to the options.
+pub async fn error_rendering_middleware(
+    req: Request<Body>,
+    next: Next,
+    options: ErrorRenderingOptions,
+) -> Response {
+    let method = req.method().to_string();
+    let instance = req.uri().path().to_string();
+    let request_id = req.extensions().get::<RequestId>().map(|x| x.to_string());
+    let locale = options.catalog.as_ref().map(|catalog| {
+        let accept_language = req
+            .headers()
+            .get(ACCEPT_LANGUAGE)
+            .and_then(|x| x.to_str().ok());
+        catalog.negotiate(accept_language)
+    });
+
+    let response = REPORTING.scope((), next.run(req)).await;
+
+    if response.status().is_server_error() {
+        options.report(error_report(
+            method,
+            instance.clone(),
+            request_id,
+            &response,
+        ));
+    }
+
+    if options.format == ErrorFormat::Json
+        && options.catalog.is_none()
+        && options.constraint_messages.is_empty()
+    {
+        return response;
+    }
+
+    let Some(mut error) = response.extensions().get::<Error>().cloned() else {
+        return response;
+    };
+
+    let (mut parts, _) = response.into_parts();
+    parts.headers.remove(CONTENT_LENGTH);
+
+    if let (Some(catalog), Some(locale)) = (&options.catalog, locale) {
+        error.localize(catalog, &locale);
+        if let Ok(locale) = HeaderValue::from_str(&locale) {
+            parts.headers.insert(CONTENT_LANGUAGE, locale);
+        }
+    }
+    apply_constraint_message(&mut error, &options.constraint_messages);
+
+    let rendered = match options.format {
+        ErrorFormat::Json => error.to_json_response(),
+        ErrorFormat::ProblemJson => error
+            .to_problem_details()
+            .with_instance(instance)
+            .into_response(),
+    };
+    let (rendered_parts, body) = rendered.into_parts();
+    parts.headers.extend(rendered_parts.headers);
+    Response::from_parts(parts, body)
+}
+
+pub trait ErrorRenderingLayer {
+    fn with_error_rendering_layer(self, options: ErrorRenderingOptions) -> Self;
+}
+
+impl<T: Clone + Send + Sync + 'static> ErrorRenderingLayer for Router<WebCoreState<T>> {
+    fn with_error_rendering_layer(self, options: ErrorRenderingOptions) -> Self {
+        self.layer(middleware::from_fn(
+            move |req: Request<Body>, next: Next| {
+                let options = options.clone();
+                async move { error_rendering_middleware(req, next, options).await }
+            },
+        ))
+    }
+}
@@ -1,5 +1,13 @@
-use crate::web_core::WebCoreState;
-use axum::Router;
+use std::sync::Arc;
+
+use crate::{forbidden, web_core::WebCoreState};
+use axum::{
+    Router,
+    body::Body,
+    extract::Request,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
 
 pub trait EnsureHeaderValueExists<T>
 where
@@ -10,31 +18,35 @@ where
     fn ensure_header_value_exists(self, key: &str, value: &str) -> Router<WebCoreState<T>>;
 }
 
+///Middleware of `ensure_header_value_exists`, also usable with `Router::route_layer` through
+///`crate_middleware_handler`.
+pub async fn ensure_header_value(
+    request: Request<Body>,
+    next: Next,
+    key: Arc<str>,
+    value: Arc<str>,
+) -> Response {
+    if let Some(header_value) = request.headers().get(key.as_ref())
+        && let Ok(header_value) = header_value.to_str()
+        && header_value == value.as_ref()
+    {
+        return next.run(request).await;
+    }
+    forbidden!().into_response()
+}
+
 impl<T> EnsureHeaderValueExists<T> for Router<WebCoreState<T>>
 where
     T: Clone + Send + Sync + 'static,
 {
-    #[allow(unused)]
     fn ensure_header_value_exists(self, key: &str, value: &str) -> Router<WebCoreState<T>> {
         use crate::web_core::WebCore;
-        use axum::{http::StatusCode, response::IntoResponse};
 
-        let key: String = key.into();
-        let value: String = value.into();
+        let key: Arc<str> = Arc::from(key);
+        let value: Arc<str> = Arc::from(value);
 
         self.with_middleware(move |request, next| {
-            let key = key.clone();
-            let value = value.clone();
-            async move {
-                if let Some(header_value) = request.headers().get(&key) {
-                    if let Ok(header_value) = header_value.to_str() {
-                        if header_value == value {
-                            return next.run(request).await;
-                        }
-                    }
-                }
-                return StatusCode::FORBIDDEN.into_response();
-            }
+            ensure_header_value(request, next, key.clone(), value.clone())
         })
     }
 }
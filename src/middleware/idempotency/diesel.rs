@@ -0,0 +1,208 @@
+#![cfg(feature = "diesel")]
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use ::diesel::{
+    OptionalExtension, PgConnection, QueryableByName, RunQueryDsl,
+    r2d2::{ConnectionManager, Pool},
+    sql_query,
+    sql_types::{BigInt, Binary, Integer, Nullable, Text},
+};
+use async_trait::async_trait;
+
+use super::{IdempotencyRecord, IdempotencyStore, StoredResponse};
+use crate::{error::Error, something_went_wrong};
+
+///Run once, or add it to your migrations.
+pub const CREATE_IDEMPOTENCY_TABLE: &str = "CREATE TABLE IF NOT EXISTS web_core_idempotency_keys (
+    key TEXT PRIMARY KEY,
+    fingerprint TEXT NOT NULL,
+    claim TEXT NOT NULL,
+    status INTEGER,
+    headers TEXT,
+    body BYTEA,
+    expires_at BIGINT NOT NULL
+)";
+
+#[derive(QueryableByName)]
+struct Row {
+    #[diesel(sql_type = Text)]
+    fingerprint: String,
+    #[diesel(sql_type = Nullable<Integer>)]
+    status: Option<i32>,
+    #[diesel(sql_type = Nullable<Text>)]
+    headers: Option<String>,
+    #[diesel(sql_type = Nullable<Binary>)]
+    body: Option<Vec<u8>>,
+}
+
+impl Row {
+    fn into_record(self) -> Result<IdempotencyRecord, Error> {
+        match self.status {
+            None => Ok(IdempotencyRecord::InFlight {
+                fingerprint: self.fingerprint,
+            }),
+            Some(status) => Ok(IdempotencyRecord::Completed {
+                fingerprint: self.fingerprint,
+                response: StoredResponse {
+                    status: status as u16,
+                    headers: serde_json::from_str(self.headers.as_deref().unwrap_or("[]"))
+                        .map_err(|e| {
+                            Error::new_something_went_wrong_with_source(
+                                "Error while reading stored headers",
+                                e,
+                            )
+                        })?,
+                    body: self.body.unwrap_or_default(),
+                },
+            }),
+        }
+    }
+}
+
+fn now_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as i64
+}
+
+///Keeps idempotency keys in Postgres, shared by every instance using the same database.
+///Expired keys are overwritten when they are used again.
+#[derive(Clone)]
+pub struct DieselIdempotencyStore {
+    pool: Pool<ConnectionManager<PgConnection>>,
+}
+
+impl DieselIdempotencyStore {
+    pub fn new(pool: Pool<ConnectionManager<PgConnection>>) -> Self {
+        Self { pool }
+    }
+
+    ///Creates `web_core_idempotency_keys` when it does not exist.
+    pub async fn create_table(&self) -> Result<(), Error> {
+        self.run(|conn| {
+            sql_query(CREATE_IDEMPOTENCY_TABLE).execute(conn)?;
+            Ok(())
+        })
+        .await
+    }
+
+    ///Deletes expired keys, eg: from a scheduled job.
+    pub async fn delete_expired(&self) -> Result<usize, Error> {
+        self.run(|conn| {
+            Ok(
+                sql_query("DELETE FROM web_core_idempotency_keys WHERE expires_at < $1")
+                    .bind::<BigInt, _>(now_ms())
+                    .execute(conn)?,
+            )
+        })
+        .await
+    }
+
+    async fn run<R: Send + 'static>(
+        &self,
+        query: impl FnOnce(&mut PgConnection) -> Result<R, Error> + Send + 'static,
+    ) -> Result<R, Error> {
+        let pool = self.pool.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut conn = pool.get()?;
+            query(&mut conn)
+        })
+        .await
+        .map_err(|e| something_went_wrong!("{:?}", e))?
+    }
+}
+
+#[async_trait]
+impl IdempotencyStore for DieselIdempotencyStore {
+    async fn begin(
+        &self,
+        key: &str,
+        fingerprint: &str,
+        claim: &str,
+        ttl: Duration,
+    ) -> Result<Option<IdempotencyRecord>, Error> {
+        let key = key.to_string();
+        let fingerprint = fingerprint.to_string();
+        let claim = claim.to_string();
+        self.run(move |conn| {
+            let now = now_ms();
+            let claimed = sql_query(
+                "INSERT INTO web_core_idempotency_keys (key, fingerprint, claim, expires_at)
+                VALUES ($1, $2, $3, $4)
+                ON CONFLICT (key) DO UPDATE SET fingerprint = EXCLUDED.fingerprint,
+                    claim = EXCLUDED.claim, status = NULL, headers = NULL, body = NULL,
+                    expires_at = EXCLUDED.expires_at
+                WHERE web_core_idempotency_keys.expires_at < $5
+                RETURNING fingerprint, status, headers, body",
+            )
+            .bind::<Text, _>(&key)
+            .bind::<Text, _>(&fingerprint)
+            .bind::<Text, _>(&claim)
+            .bind::<BigInt, _>(now + ttl.as_millis() as i64)
+            .bind::<BigInt, _>(now)
+            .get_result::<Row>(conn)
+            .optional()?;
+            if claimed.is_some() {
+                return Ok(None);
+            }
+            let existing = sql_query(
+                "SELECT fingerprint, status, headers, body FROM web_core_idempotency_keys
+                WHERE key = $1",
+            )
+            .bind::<Text, _>(&key)
+            .get_result::<Row>(conn)
+            .optional()?;
+            match existing {
+                Some(existing) => Ok(Some(existing.into_record()?)),
+                //Released between the two queries, the client can retry.
+                None => Ok(Some(IdempotencyRecord::InFlight { fingerprint })),
+            }
+        })
+        .await
+    }
+
+    async fn complete(
+        &self,
+        key: &str,
+        claim: &str,
+        response: StoredResponse,
+        ttl: Duration,
+    ) -> Result<(), Error> {
+        let key = key.to_string();
+        let claim = claim.to_string();
+        self.run(move |conn| {
+            let headers = serde_json::to_string(&response.headers).map_err(|e| {
+                Error::new_something_went_wrong_with_source("Error while storing headers", e)
+            })?;
+            sql_query(
+                "UPDATE web_core_idempotency_keys
+                SET status = $3, headers = $4, body = $5, expires_at = $6
+                WHERE key = $1 AND claim = $2",
+            )
+            .bind::<Text, _>(&key)
+            .bind::<Text, _>(&claim)
+            .bind::<Integer, _>(response.status as i32)
+            .bind::<Text, _>(headers)
+            .bind::<Binary, _>(&response.body)
+            .bind::<BigInt, _>(now_ms() + ttl.as_millis() as i64)
+            .execute(conn)?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn release(&self, key: &str, claim: &str) -> Result<(), Error> {
+        let key = key.to_string();
+        let claim = claim.to_string();
+        self.run(move |conn| {
+            sql_query("DELETE FROM web_core_idempotency_keys WHERE key = $1 AND claim = $2")
+                .bind::<Text, _>(&key)
+                .bind::<Text, _>(&claim)
+                .execute(conn)?;
+            Ok(())
+        })
+        .await
+    }
+}
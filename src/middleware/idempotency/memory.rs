@@ -0,0 +1,110 @@
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+
+use super::{IdempotencyRecord, IdempotencyStore, StoredResponse};
+use crate::error::Error;
+
+///Expired keys are dropped once this many exist, at most once per `PRUNE_INTERVAL`.
+const PRUNE_THRESHOLD: usize = 10_000;
+const PRUNE_INTERVAL: Duration = Duration::from_secs(60);
+
+struct Entry {
+    record: IdempotencyRecord,
+    claim: String,
+    expires_at: Instant,
+}
+
+struct Entries {
+    entries: HashMap<String, Entry>,
+    pruned_at: Instant,
+}
+
+impl Default for Entries {
+    fn default() -> Self {
+        Self {
+            entries: HashMap::new(),
+            pruned_at: Instant::now(),
+        }
+    }
+}
+
+///Keeps idempotency keys in process memory. Keys are not shared between instances.
+#[derive(Default)]
+pub struct MemoryIdempotencyStore {
+    entries: Mutex<Entries>,
+}
+
+impl MemoryIdempotencyStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl IdempotencyStore for MemoryIdempotencyStore {
+    async fn begin(
+        &self,
+        key: &str,
+        fingerprint: &str,
+        claim: &str,
+        ttl: Duration,
+    ) -> Result<Option<IdempotencyRecord>, Error> {
+        let now = Instant::now();
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        if entries.entries.len() >= PRUNE_THRESHOLD
+            && now.duration_since(entries.pruned_at) >= PRUNE_INTERVAL
+        {
+            entries.entries.retain(|_, x| x.expires_at > now);
+            entries.pruned_at = now;
+        }
+        if let Some(entry) = entries.entries.get(key)
+            && entry.expires_at > now
+        {
+            return Ok(Some(entry.record.clone()));
+        }
+        entries.entries.insert(
+            key.to_string(),
+            Entry {
+                record: IdempotencyRecord::InFlight {
+                    fingerprint: fingerprint.to_string(),
+                },
+                claim: claim.to_string(),
+                expires_at: now + ttl,
+            },
+        );
+        Ok(None)
+    }
+
+    async fn complete(
+        &self,
+        key: &str,
+        claim: &str,
+        response: StoredResponse,
+        ttl: Duration,
+    ) -> Result<(), Error> {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(entry) = entries.entries.get_mut(key)
+            && entry.claim == claim
+        {
+            entry.record = IdempotencyRecord::Completed {
+                fingerprint: entry.record.fingerprint().to_string(),
+                response,
+            };
+            entry.expires_at = Instant::now() + ttl;
+        }
+        Ok(())
+    }
+
+    async fn release(&self, key: &str, claim: &str) -> Result<(), Error> {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        if entries.entries.get(key).is_some_and(|x| x.claim == claim) {
+            entries.entries.remove(key);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,379 @@
+pub mod diesel;
+pub mod memory;
+
+use std::{sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use axum::{
+    Router,
+    body::{Body, Bytes, HttpBody, to_bytes},
+    extract::MatchedPath,
+    http::{
+        HeaderName, HeaderValue, Method, Request, StatusCode,
+        header::{CONNECTION, CONTENT_LENGTH, DATE, TRANSFER_ENCODING},
+    },
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    auth::authenticated_user::AuthenticatedUser,
+    error::{Error, code::CoreErrorCode},
+    extract::ClientIp,
+    web_core::WebCoreState,
+};
+
+#[cfg(feature = "diesel")]
+pub use diesel::DieselIdempotencyStore;
+pub use memory::MemoryIdempotencyStore;
+
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+pub const IDEMPOTENT_REPLAYED_HEADER: &str = "idempotent-replayed";
+
+const MAX_KEY_LENGTH: usize = 255;
+
+///A response as it was first sent, replayed for retries of the same request.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct StoredResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl StoredResponse {
+    fn into_response(self) -> Response {
+        let mut response = Response::new(Body::from(self.body));
+        *response.status_mut() = StatusCode::from_u16(self.status).unwrap_or(StatusCode::OK);
+        for (name, value) in self.headers {
+            if let (Ok(name), Ok(value)) = (
+                HeaderName::from_bytes(name.as_bytes()),
+                HeaderValue::from_str(&value),
+            ) {
+                response.headers_mut().append(name, value);
+            }
+        }
+        response
+            .headers_mut()
+            .insert(IDEMPOTENT_REPLAYED_HEADER, HeaderValue::from_static("true"));
+        response
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IdempotencyRecord {
+    ///The first request with the key is still being handled.
+    InFlight { fingerprint: String },
+    Completed {
+        fingerprint: String,
+        response: StoredResponse,
+    },
+}
+
+impl IdempotencyRecord {
+    pub fn fingerprint(&self) -> &str {
+        match self {
+            IdempotencyRecord::InFlight { fingerprint }
+            | IdempotencyRecord::Completed { fingerprint, .. } => fingerprint,
+        }
+    }
+}
+
+///Where idempotency keys are kept. Use a shared store such as `DieselIdempotencyStore` when
+///running more than one instance.
+///
+///`claim` is unique to each request. `complete` and `release` only act on a key still held by
+///the same claim, so a request that outlived its ttl can not touch the key of a later one.
+#[async_trait]
+pub trait IdempotencyStore: Send + Sync {
+    ///Claims `key` for a new request for `ttl`. Returns the existing record instead when the key
+    ///is already in use and has not expired.
+    async fn begin(
+        &self,
+        key: &str,
+        fingerprint: &str,
+        claim: &str,
+        ttl: Duration,
+    ) -> Result<Option<IdempotencyRecord>, Error>;
+
+    ///Stores the response of a claimed key.
+    async fn complete(
+        &self,
+        key: &str,
+        claim: &str,
+        response: StoredResponse,
+        ttl: Duration,
+    ) -> Result<(), Error>;
+
+    ///Frees a claimed key so that the request can be retried, eg: after a 5xx.
+    async fn release(&self, key: &str, claim: &str) -> Result<(), Error>;
+}
+
+#[derive(Clone)]
+pub struct IdempotencyOptions {
+    pub store: Arc<dyn IdempotencyStore>,
+    ///How long a key is remembered. Defaults to 24 hours.
+    pub ttl: Duration,
+    ///How long a key stays claimed when the first request never completes, eg: the instance
+    ///crashed. Defaults to 5 minutes.
+    pub in_flight_ttl: Duration,
+    ///Defaults to `POST` and `PATCH`.
+    pub methods: Vec<Method>,
+    ///Rejects requests without an `Idempotency-Key` with 400 instead of handling them normally.
+    pub required: bool,
+    ///Larger requests are rejected with 413 and larger responses are not stored.
+    pub max_body_bytes: usize,
+}
+
+impl IdempotencyOptions {
+    pub fn new(store: impl IdempotencyStore + 'static) -> Self {
+        Self {
+            store: Arc::new(store),
+            ttl: Duration::from_secs(24 * 60 * 60),
+            in_flight_ttl: Duration::from_secs(5 * 60),
+            methods: vec![Method::POST, Method::PATCH],
+            required: false,
+            max_body_bytes: 1024 * 1024,
+        }
+    }
+
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    pub fn with_in_flight_ttl(mut self, in_flight_ttl: Duration) -> Self {
+        self.in_flight_ttl = in_flight_ttl;
+        self
+    }
+
+    pub fn with_methods(mut self, methods: impl IntoIterator<Item = Method>) -> Self {
+        self.methods = methods.into_iter().collect();
+        self
+    }
+
+    pub fn required(mut self) -> Self {
+        self.required = true;
+        self
+    }
+
+    pub fn with_max_body_bytes(mut self, max_body_bytes: usize) -> Self {
+        self.max_body_bytes = max_body_bytes;
+        self
+    }
+}
+
+fn sha256_hex(parts: &[&[u8]]) -> String {
+    let mut hasher = Sha256::new();
+    for part in parts {
+        hasher.update((part.len() as u64).to_be_bytes());
+        hasher.update(part);
+    }
+    hex::encode(hasher.finalize())
+}
+
+///Key and claim of the request being handled.
+#[derive(Default)]
+struct Claim {
+    key: String,
+    claim: String,
+}
+
+async fn release(store: &dyn IdempotencyStore, claim: &Claim) {
+    if let Err(e) = store.release(&claim.key, &claim.claim).await {
+        tracing::warn!(error = ?e, "idempotency key could not be released");
+    }
+}
+
+///Frees a claimed key when dropped before `disarm`, eg: the handler panicked or the client went
+///away, so that retries are not answered 409 until `in_flight_ttl` expires.
+struct ReleaseGuard {
+    store: Option<Arc<dyn IdempotencyStore>>,
+    claim: Claim,
+}
+
+impl ReleaseGuard {
+    fn new(store: Arc<dyn IdempotencyStore>, claim: Claim) -> Self {
+        Self {
+            store: Some(store),
+            claim,
+        }
+    }
+
+    fn disarm(mut self) {
+        self.store = None;
+    }
+}
+
+impl Drop for ReleaseGuard {
+    fn drop(&mut self) {
+        let Some(store) = self.store.take() else {
+            return;
+        };
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            return;
+        };
+        let claim = std::mem::take(&mut self.claim);
+        runtime.spawn(async move { release(store.as_ref(), &claim).await });
+    }
+}
+
+///Stores the response, or frees the key when the response should not be replayed.
+///The response is sent either way, since the request has already been handled.
+async fn store_response(
+    options: &IdempotencyOptions,
+    claim: &Claim,
+    response: Response,
+) -> Response {
+    let too_large = HttpBody::size_hint(response.body())
+        .upper()
+        .is_none_or(|x| x > options.max_body_bytes as u64);
+    if response.status().is_server_error() || too_large {
+        release(options.store.as_ref(), claim).await;
+        return response;
+    }
+
+    let (parts, body) = response.into_parts();
+    let body: Bytes = match to_bytes(body, options.max_body_bytes).await {
+        Ok(body) => body,
+        Err(e) => {
+            release(options.store.as_ref(), claim).await;
+            return Error::new_something_went_wrong_with_source(
+                "Error while reading response body",
+                e,
+            )
+            .into_response();
+        }
+    };
+    let headers = parts
+        .headers
+        .iter()
+        .filter(|(name, _)| ![CONTENT_LENGTH, TRANSFER_ENCODING, CONNECTION, DATE].contains(name))
+        .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+        .collect();
+    let stored = StoredResponse {
+        status: parts.status.as_u16(),
+        headers,
+        body: body.to_vec(),
+    };
+    if let Err(e) = options
+        .store
+        .complete(&claim.key, &claim.claim, stored, options.ttl)
+        .await
+    {
+        tracing::warn!(error = ?e, "idempotent response could not be stored");
+    }
+    Response::from_parts(parts, Body::from(body))
+}
+
+async fn handle(
+    req: Request<Body>,
+    next: Next,
+    options: &IdempotencyOptions,
+) -> Result<Response, Error> {
+    if !options.methods.contains(req.method()) {
+        return Ok(next.run(req).await);
+    }
+    let Some(idempotency_key) = req.headers().get(IDEMPOTENCY_KEY_HEADER) else {
+        if options.required {
+            return Err(
+                Error::bad_request_error("Header `Idempotency-Key` is missing.")
+                    .with_code(CoreErrorCode::MissingHeader),
+            );
+        }
+        return Ok(next.run(req).await);
+    };
+    let idempotency_key = idempotency_key
+        .to_str()
+        .ok()
+        .filter(|x| !x.is_empty() && x.len() <= MAX_KEY_LENGTH)
+        .ok_or_else(|| {
+            Error::bad_request_error("Header `Idempotency-Key` is invalid.")
+                .with_code(CoreErrorCode::InvalidHeader)
+        })?
+        .to_string();
+
+    //Anonymous callers are told apart by their address, rather than all sharing one subject.
+    let subject = match (
+        req.extensions().get::<AuthenticatedUser>(),
+        req.extensions().get::<ClientIp>(),
+    ) {
+        (Some(user), _) => format!("subject:{}", user.subject),
+        (None, Some(client_ip)) => format!("ip:{client_ip}"),
+        (None, None) => String::from("anonymous"),
+    };
+    let route = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|x| x.as_str().to_string())
+        .unwrap_or_else(|| req.uri().path().to_string());
+    let key = sha256_hex(&[
+        subject.as_bytes(),
+        req.method().as_str().as_bytes(),
+        route.as_bytes(),
+        idempotency_key.as_bytes(),
+    ]);
+
+    let (parts, body) = req.into_parts();
+    let body = to_bytes(body, options.max_body_bytes)
+        .await
+        .map_err(|_| Error::new_payload_too_large("Request body is too large."))?;
+    let uri = parts.uri.to_string();
+    let fingerprint = sha256_hex(&[uri.as_bytes(), &body]);
+    let claim = uuid::Uuid::new_v4().to_string();
+
+    match options
+        .store
+        .begin(&key, &fingerprint, &claim, options.in_flight_ttl)
+        .await?
+    {
+        Some(record) if record.fingerprint() != fingerprint => {
+            Err(Error::new_unprocessable_entity(
+                "Idempotency-Key was already used with a different request.",
+            )
+            .with_code(CoreErrorCode::IdempotencyKeyReused))
+        }
+        Some(IdempotencyRecord::InFlight { .. }) => Err(Error::new_conflict(
+            "A request with this Idempotency-Key is still being processed.",
+        )
+        .with_code(CoreErrorCode::IdempotencyInFlight)),
+        Some(IdempotencyRecord::Completed { response, .. }) => Ok(response.into_response()),
+        None => {
+            let claim = Claim { key, claim };
+            let guard = ReleaseGuard::new(options.store.clone(), claim);
+            let response = next.run(Request::from_parts(parts, Body::from(body))).await;
+            let response = store_response(options, &guard.claim, response).await;
+            guard.disarm();
+            Ok(response)
+        }
+    }
+}
+
+///Replays the first response for retries carrying the same `Idempotency-Key`, keyed by
+///key, subject and route. Add the layer before `with_auth_layer` so that the subject is known,
+///anonymous requests are keyed by their `ClientIp` instead.
+pub async fn idempotency_middleware(
+    req: Request<Body>,
+    next: Next,
+    options: IdempotencyOptions,
+) -> Response {
+    match handle(req, next, &options).await {
+        Ok(response) => response,
+        Err(e) => e.into_response(),
+    }
+}
+
+pub trait IdempotencyLayer {
+    fn with_idempotency_layer(self, options: IdempotencyOptions) -> Self;
+}
+
+impl<T: Clone + Send + Sync + 'static> IdempotencyLayer for Router<WebCoreState<T>> {
+    fn with_idempotency_layer(self, options: IdempotencyOptions) -> Self {
+        self.layer(middleware::from_fn(
+            move |req: Request<Body>, next: Next| {
+                let options = options.clone();
+                async move { idempotency_middleware(req, next, options).await }
+            },
+        ))
+    }
+}
@@ -1,41 +1,270 @@
 use std::time::Instant;
 
-use axum::{body::Body, http::Request, middleware::{self, Next}, response::Response, Router};
+use axum::{
+    Router,
+    body::{Body, HttpBody, to_bytes},
+    extract::MatchedPath,
+    http::{
+        HeaderMap, Request,
+        header::{CONTENT_LENGTH, CONTENT_TYPE},
+    },
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+};
+use rand_core::{OsRng, RngCore};
+use tracing::{Instrument, field::Empty};
+use tracing_subscriber::EnvFilter;
 
-use crate::web_core::WebCoreState;
+use crate::{
+    auth::authenticated_user::AuthenticatedUser,
+    error::Error,
+    extract::ClientIp,
+    middleware::{redaction::Redaction, request_id::RequestId},
+    web_core::WebCoreState,
+};
 
-pub async fn logging_middleware(req: Request<Body>, next: Next) -> Response {
+///Output format of `init_logging`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LogFormat {
+    ///Human readable, multi line output for local development.
+    #[default]
+    Pretty,
+    ///One json object per line, for log aggregators.
+    Json,
+}
+
+///Installs a global `tracing` subscriber writing to stdout. The level is taken from `RUST_LOG`
+///and defaults to `info`. Does nothing if a subscriber is already installed.
+pub fn init_logging(format: LogFormat) {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    let builder = tracing_subscriber::fmt().with_env_filter(filter);
+    let _ = match format {
+        LogFormat::Pretty => builder.pretty().try_init(),
+        LogFormat::Json => builder
+            .json()
+            .flatten_event(true)
+            .with_current_span(true)
+            .with_span_list(false)
+            .try_init(),
+    };
+}
+
+///Header and body logging is off by default. When turned on, sensitive headers and fields are
+///redacted using `redaction`.
+#[derive(Debug, Clone)]
+pub struct LoggingOptions {
+    pub log_headers: bool,
+    pub log_bodies: bool,
+    pub redaction: Redaction,
+    ///Logged bodies are cut after this many bytes.
+    pub max_body_bytes: usize,
+    ///Bodies are only buffered when their declared length is at most this, so that streams and
+    ///uploads are left alone.
+    pub max_buffered_bytes: usize,
+    ///Fraction of requests, from `0.0` to `1.0`, whose headers and bodies are logged.
+    pub sample_rate: f64,
+}
+
+impl Default for LoggingOptions {
+    fn default() -> Self {
+        Self {
+            log_headers: false,
+            log_bodies: false,
+            redaction: Redaction::default(),
+            max_body_bytes: 4 * 1024,
+            max_buffered_bytes: 64 * 1024,
+            sample_rate: 1.0,
+        }
+    }
+}
+
+impl LoggingOptions {
+    pub fn with_headers(mut self) -> Self {
+        self.log_headers = true;
+        self
+    }
+
+    pub fn with_bodies(mut self) -> Self {
+        self.log_bodies = true;
+        self
+    }
+
+    pub fn with_redaction(mut self, redaction: Redaction) -> Self {
+        self.redaction = redaction;
+        self
+    }
+
+    pub fn with_max_body_bytes(mut self, max_body_bytes: usize) -> Self {
+        self.max_body_bytes = max_body_bytes;
+        self
+    }
+
+    pub fn with_max_buffered_bytes(mut self, max_buffered_bytes: usize) -> Self {
+        self.max_buffered_bytes = max_buffered_bytes;
+        self
+    }
+
+    pub fn with_sample_rate(mut self, sample_rate: f64) -> Self {
+        self.sample_rate = sample_rate.clamp(0.0, 1.0);
+        self
+    }
+
+    fn is_sampled(&self) -> bool {
+        if !self.log_headers && !self.log_bodies {
+            return false;
+        }
+        self.sample_rate >= 1.0 || (OsRng.next_u32() as f64 / u32::MAX as f64) < self.sample_rate
+    }
+
+    fn content_type(headers: &HeaderMap) -> Option<&str> {
+        headers.get(CONTENT_TYPE).and_then(|x| x.to_str().ok())
+    }
+
+    ///Buffers the body when it is small enough, returning it with the text to log.
+    async fn capture(
+        &self,
+        headers: &HeaderMap,
+        body: Body,
+        length: Option<u64>,
+    ) -> Result<(Body, String), axum::Error> {
+        match length {
+            Some(length) if length <= self.max_buffered_bytes as u64 => {
+                let bytes = to_bytes(body, self.max_buffered_bytes).await?;
+                let logged =
+                    self.redaction
+                        .body(Self::content_type(headers), &bytes, self.max_body_bytes);
+                Ok((Body::from(bytes), logged))
+            }
+            _ => Ok((body, String::from("<not captured>"))),
+        }
+    }
+}
+
+///Opens a `request` span around the rest of the stack and logs one event when the response is ready.
+pub async fn logging_middleware(
+    mut req: Request<Body>,
+    next: Next,
+    options: LoggingOptions,
+) -> Response {
     let start = Instant::now();
 
-    let method = req.method().clone();
-    let uri = req.uri().clone();
+    let route = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|x| x.as_str().to_string())
+        .unwrap_or_else(|| req.uri().path().to_string());
+    let request_id = req
+        .extensions()
+        .get::<RequestId>()
+        .map(|x| x.to_string())
+        .unwrap_or_default();
+
+    let client_ip = req
+        .extensions()
+        .get::<ClientIp>()
+        .map(|x| x.to_string())
+        .unwrap_or_else(|| String::from("unknown"));
+
+    let span = tracing::info_span!(
+        "request",
+        method = %req.method(),
+        route = %route,
+        request_id = %request_id,
+        client_ip = %client_ip,
+        status = Empty,
+        latency_ms = Empty,
+        subject = Empty,
+        request_headers = Empty,
+        request_body = Empty,
+        response_headers = Empty,
+        response_body = Empty,
+    );
 
-    let headers = req.headers().clone();
+    let sampled = options.is_sampled();
+    if sampled && options.log_headers {
+        span.record("request_headers", options.redaction.headers(req.headers()));
+    }
+    if sampled && options.log_bodies {
+        let (parts, body) = req.into_parts();
+        let length = parts
+            .headers
+            .get(CONTENT_LENGTH)
+            .and_then(|x| x.to_str().ok())
+            .and_then(|x| x.parse::<u64>().ok());
+        let (body, logged) = match options.capture(&parts.headers, body, length).await {
+            Ok(captured) => captured,
+            Err(e) => {
+                return Error::bad_request_error(&format!(
+                    "Error while reading request body : {e}"
+                ))
+                .into_response();
+            }
+        };
+        span.record("request_body", logged);
+        req = Request::from_parts(parts, body);
+    }
 
-    let client_ip = headers
-        .get("x-forwarded-for")
-        .and_then(|h| h.to_str().ok())
-        .unwrap_or("unknown");
+    let mut response = next.run(req).instrument(span.clone()).await;
 
-    let response = next.run(req).await;
+    if sampled && options.log_headers {
+        span.record(
+            "response_headers",
+            options.redaction.headers(response.headers()),
+        );
+    }
+    if sampled && options.log_bodies {
+        let (parts, body) = response.into_parts();
+        let length = HttpBody::size_hint(&body).exact();
+        let (body, logged) = match options.capture(&parts.headers, body, length).await {
+            Ok(captured) => captured,
+            Err(e) => {
+                return Error::new_something_went_wrong_with_source(
+                    "Error while reading response body",
+                    e,
+                )
+                .into_response();
+            }
+        };
+        span.record("response_body", logged);
+        response = Response::from_parts(parts, body);
+    }
 
     let status = response.status();
-    let elapsed = start.elapsed();
+    span.record("status", status.as_u16());
+    span.record("latency_ms", start.elapsed().as_millis() as u64);
+    if let Some(user) = response.extensions().get::<AuthenticatedUser>() {
+        span.record("subject", user.subject.as_str());
+    }
 
-    println!(
-        "[{}] {} {} from {} in {:?}",
-        status, method, uri, client_ip, elapsed
-    );
+    span.in_scope(|| {
+        if status.is_server_error() {
+            tracing::error!("request failed");
+        } else if status.is_client_error() {
+            tracing::warn!("request rejected");
+        } else {
+            tracing::info!("request completed");
+        }
+    });
 
     response
 }
 
 pub trait LoggingMiddlewareLayer {
-    fn with_logging_layer(self)  -> Self;
+    fn with_logging_layer(self) -> Self;
+    fn with_logging_layer_options(self, options: LoggingOptions) -> Self;
 }
 
 impl<T: Clone + Send + Sync + 'static> LoggingMiddlewareLayer for Router<WebCoreState<T>> {
-    fn with_logging_layer(self)  -> Self {
-        self.layer(middleware::from_fn(logging_middleware))
+    fn with_logging_layer(self) -> Self {
+        self.with_logging_layer_options(LoggingOptions::default())
     }
-}
\ No newline at end of file
+
+    fn with_logging_layer_options(self, options: LoggingOptions) -> Self {
+        self.layer(middleware::from_fn(
+            move |req: Request<Body>, next: Next| {
+                let options = options.clone();
+                async move { logging_middleware(req, next, options).await }
+            },
+        ))
+    }
+}
@@ -7,6 +7,7 @@ use axum::{
     response::Response,
 };
 
+#[allow(clippy::type_complexity)]
 pub fn crate_middleware_handler<F, Fut>(
     header_handler: F,
 ) -> FromFnLayer<
@@ -1,3 +1,12 @@
+pub mod catch_panic;
+pub mod client_ip;
+pub mod error_rendering;
 pub mod headers;
+pub mod idempotency;
 pub mod logging_middleware;
 pub mod middleware_handler;
+pub mod rate_limit;
+pub mod redaction;
+pub mod request_id;
+pub mod request_limits;
+pub mod security_headers;
@@ -0,0 +1,136 @@
+#![cfg(feature = "diesel")]
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use ::diesel::{
+    PgConnection, QueryableByName, RunQueryDsl,
+    r2d2::{ConnectionManager, Pool},
+    sql_query,
+    sql_types::{BigInt, Text},
+};
+use async_trait::async_trait;
+
+use super::{Quota, RateLimitDecision, RateLimitStore};
+use crate::{error::Error, something_went_wrong};
+
+///Run once, or add it to your migrations.
+pub const CREATE_RATE_LIMIT_TABLE: &str = "CREATE TABLE IF NOT EXISTS web_core_rate_limits (
+    key TEXT NOT NULL,
+    window_start BIGINT NOT NULL,
+    hits BIGINT NOT NULL,
+    PRIMARY KEY (key, window_start)
+)";
+
+#[derive(QueryableByName)]
+struct Hits {
+    #[diesel(sql_type = BigInt)]
+    hits: i64,
+}
+
+///Sliding window counter in Postgres, shared by every instance using the same database.
+///
+///Hits are counted in fixed windows and the previous window is weighted by how much of it still
+///overlaps the sliding window. Older windows of a key are deleted when it starts a new window,
+///see `delete_expired` for keys which do not come back.
+#[derive(Clone)]
+pub struct DieselRateLimitStore {
+    pool: Pool<ConnectionManager<PgConnection>>,
+}
+
+impl DieselRateLimitStore {
+    pub fn new(pool: Pool<ConnectionManager<PgConnection>>) -> Self {
+        Self { pool }
+    }
+
+    ///Creates `web_core_rate_limits` when it does not exist.
+    pub async fn create_table(&self) -> Result<(), Error> {
+        let pool = self.pool.clone();
+        tokio::task::spawn_blocking(move || -> Result<(), Error> {
+            let mut conn = pool.get()?;
+            sql_query(CREATE_RATE_LIMIT_TABLE).execute(&mut conn)?;
+            Ok(())
+        })
+        .await
+        .map_err(|e| something_went_wrong!("{:?}", e))?
+    }
+
+    ///Deletes counters no longer used by any sliding window, eg: from a scheduled job.
+    ///`longest_window` is the longest quota window used with the store.
+    pub async fn delete_expired(&self, longest_window: Duration) -> Result<usize, Error> {
+        let pool = self.pool.clone();
+        let now_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as i64;
+        //The previous window is still read, so counters are kept for two windows.
+        let expired_before = now_ms - 2 * longest_window.as_millis().max(1) as i64;
+        tokio::task::spawn_blocking(move || -> Result<usize, Error> {
+            let mut conn = pool.get()?;
+            Ok(
+                sql_query("DELETE FROM web_core_rate_limits WHERE window_start < $1")
+                    .bind::<BigInt, _>(expired_before)
+                    .execute(&mut conn)?,
+            )
+        })
+        .await
+        .map_err(|e| something_went_wrong!("{:?}", e))?
+    }
+}
+
+#[async_trait]
+impl RateLimitStore for DieselRateLimitStore {
+    async fn hit(&self, key: &str, quota: &Quota) -> Result<RateLimitDecision, Error> {
+        let pool = self.pool.clone();
+        let key = key.to_string();
+        let window_ms = quota.window.as_millis().max(1) as i64;
+        let now_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as i64;
+        let window_start = now_ms - now_ms % window_ms;
+
+        let (current, previous) = tokio::task::spawn_blocking(move || -> Result<_, Error> {
+            let mut conn = pool.get()?;
+            let current = sql_query(
+                "INSERT INTO web_core_rate_limits (key, window_start, hits) VALUES ($1, $2, 1)
+                ON CONFLICT (key, window_start) DO UPDATE SET hits = web_core_rate_limits.hits + 1
+                RETURNING hits",
+            )
+            .bind::<Text, _>(&key)
+            .bind::<BigInt, _>(window_start)
+            .get_result::<Hits>(&mut conn)?
+            .hits;
+            let previous = sql_query(
+                "SELECT hits FROM web_core_rate_limits WHERE key = $1 AND window_start = $2",
+            )
+            .bind::<Text, _>(&key)
+            .bind::<BigInt, _>(window_start - window_ms)
+            .get_results::<Hits>(&mut conn)?
+            .first()
+            .map(|x| x.hits)
+            .unwrap_or_default();
+            if current == 1 {
+                sql_query("DELETE FROM web_core_rate_limits WHERE key = $1 AND window_start < $2")
+                    .bind::<Text, _>(&key)
+                    .bind::<BigInt, _>(window_start - window_ms)
+                    .execute(&mut conn)?;
+            }
+            Ok((current, previous))
+        })
+        .await
+        .map_err(|e| something_went_wrong!("{:?}", e))??;
+
+        let elapsed_ms = now_ms - window_start;
+        let previous_weight = (window_ms - elapsed_ms) as f64 / window_ms as f64;
+        let estimated = previous as f64 * previous_weight + current as f64;
+        let limit = quota.limit as f64;
+        let allowed = estimated <= limit;
+
+        Ok(RateLimitDecision {
+            allowed,
+            limit: quota.limit,
+            remaining: (limit - estimated).max(0.0).floor() as u32,
+            reset_after: Duration::from_millis((window_ms - elapsed_ms) as u64),
+        })
+    }
+}
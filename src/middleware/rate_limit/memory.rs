@@ -0,0 +1,118 @@
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+
+use super::{Quota, RateLimitDecision, RateLimitStore};
+use crate::error::Error;
+
+///Full buckets are dropped once this many exist, at most once per `PRUNE_INTERVAL`.
+const PRUNE_THRESHOLD: usize = 10_000;
+const PRUNE_INTERVAL: Duration = Duration::from_secs(60);
+
+struct Bucket {
+    tokens: f64,
+    updated_at: Instant,
+    ///Quota the bucket was last hit with, as keys of different quotas share the store.
+    capacity: f64,
+    refill_per_second: f64,
+}
+
+impl Bucket {
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.duration_since(self.updated_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.refill_per_second).min(self.capacity);
+        self.updated_at = now;
+    }
+
+    fn is_full(&self, now: Instant) -> bool {
+        let elapsed = now.duration_since(self.updated_at).as_secs_f64();
+        self.tokens + elapsed * self.refill_per_second >= self.capacity
+    }
+}
+
+struct Buckets {
+    buckets: HashMap<String, Bucket>,
+    pruned_at: Instant,
+}
+
+impl Default for Buckets {
+    fn default() -> Self {
+        Self {
+            buckets: HashMap::new(),
+            pruned_at: Instant::now(),
+        }
+    }
+}
+
+///Token bucket per key, kept in process memory. Each bucket holds `quota.limit` tokens and
+///refills at `quota.limit` per `quota.window`, so short bursts are allowed.
+///Counters are not shared between instances.
+#[derive(Default)]
+pub struct MemoryRateLimitStore {
+    buckets: Mutex<Buckets>,
+}
+
+impl MemoryRateLimitStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl RateLimitStore for MemoryRateLimitStore {
+    async fn hit(&self, key: &str, quota: &Quota) -> Result<RateLimitDecision, Error> {
+        //Nothing is ever refilled, and computing when would divide by zero.
+        if quota.limit == 0 {
+            return Ok(RateLimitDecision {
+                allowed: false,
+                limit: 0,
+                remaining: 0,
+                reset_after: quota.window,
+            });
+        }
+
+        let capacity = quota.limit as f64;
+        let refill_per_second = capacity / quota.window.as_secs_f64().max(f64::EPSILON);
+        let now = Instant::now();
+
+        let mut state = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        if state.buckets.len() >= PRUNE_THRESHOLD
+            && now.duration_since(state.pruned_at) >= PRUNE_INTERVAL
+        {
+            state.buckets.retain(|_, bucket| !bucket.is_full(now));
+            state.pruned_at = now;
+        }
+
+        let bucket = state.buckets.entry(key.to_string()).or_insert(Bucket {
+            tokens: capacity,
+            updated_at: now,
+            capacity,
+            refill_per_second,
+        });
+        bucket.refill(now);
+        bucket.capacity = capacity;
+        bucket.refill_per_second = refill_per_second;
+        bucket.tokens = bucket.tokens.min(capacity);
+
+        let allowed = bucket.tokens >= 1.0;
+        if allowed {
+            bucket.tokens -= 1.0;
+        }
+        let missing = if allowed {
+            capacity - bucket.tokens
+        } else {
+            1.0 - bucket.tokens
+        };
+
+        Ok(RateLimitDecision {
+            allowed,
+            limit: quota.limit,
+            remaining: bucket.tokens.floor() as u32,
+            reset_after: Duration::from_secs_f64(missing / refill_per_second),
+        })
+    }
+}
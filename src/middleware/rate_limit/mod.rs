@@ -0,0 +1,225 @@
+pub mod diesel;
+pub mod memory;
+
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use axum::{
+    Router,
+    body::Body,
+    extract::MatchedPath,
+    http::{HeaderName, HeaderValue, Request, request::Parts},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    auth::authenticated_user::AuthenticatedUser, error::Error, extract::ClientIp,
+    web_core::WebCoreState,
+};
+
+#[cfg(feature = "diesel")]
+pub use diesel::DieselRateLimitStore;
+pub use memory::MemoryRateLimitStore;
+
+const RATE_LIMIT_LIMIT: &str = "ratelimit-limit";
+const RATE_LIMIT_REMAINING: &str = "ratelimit-remaining";
+const RATE_LIMIT_RESET: &str = "ratelimit-reset";
+const RATE_LIMIT_POLICY: &str = "ratelimit-policy";
+
+///`limit` requests per `window`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Quota {
+    pub limit: u32,
+    pub window: Duration,
+}
+
+impl Quota {
+    pub fn new(limit: u32, window: Duration) -> Self {
+        Self { limit, window }
+    }
+
+    pub fn per_second(limit: u32) -> Self {
+        Self::new(limit, Duration::from_secs(1))
+    }
+
+    pub fn per_minute(limit: u32) -> Self {
+        Self::new(limit, Duration::from_secs(60))
+    }
+
+    pub fn per_hour(limit: u32) -> Self {
+        Self::new(limit, Duration::from_secs(60 * 60))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimitDecision {
+    pub allowed: bool,
+    pub limit: u32,
+    pub remaining: u32,
+    ///When denied, the time until the next request is allowed. Otherwise the time until the
+    ///quota is fully available again.
+    pub reset_after: Duration,
+}
+
+///Where hits are counted. Use a shared store such as `DieselRateLimitStore` when running more
+///than one instance.
+#[async_trait]
+pub trait RateLimitStore: Send + Sync {
+    ///Counts one request for `key` against `quota`.
+    async fn hit(&self, key: &str, quota: &Quota) -> Result<RateLimitDecision, Error>;
+}
+
+type KeyFn = Arc<dyn Fn(&Parts) -> Option<String> + Send + Sync>;
+
+///What a quota is counted per. Requests without a key are not limited.
+#[derive(Clone)]
+pub enum RateLimitKey {
+    ClientIp,
+    ///The authenticated subject, falling back to the client ip for anonymous requests.
+    ///The rate limit layer has to be added before `with_auth_layer` so that it runs after it.
+    Subject,
+    ///Value of the given header, falling back to the client ip when it is missing. The value is
+    ///hashed before it reaches the store.
+    ///
+    ///Every distinct value gets its own quota, so the key has to be checked by an earlier layer,
+    ///eg: `ensure_header_value_exists`. Otherwise a caller sending random keys is never limited.
+    ApiKey(HeaderName),
+    Custom(KeyFn),
+}
+
+impl RateLimitKey {
+    pub fn custom(key: impl Fn(&Parts) -> Option<String> + Send + Sync + 'static) -> Self {
+        Self::Custom(Arc::new(key))
+    }
+
+    fn resolve(&self, parts: &Parts) -> Option<String> {
+        let client_ip = || {
+            parts
+                .extensions
+                .get::<ClientIp>()
+                .map(|x| format!("ip:{x}"))
+        };
+        match self {
+            RateLimitKey::ClientIp => client_ip(),
+            RateLimitKey::Subject => parts
+                .extensions
+                .get::<AuthenticatedUser>()
+                .map(|x| format!("sub:{}", x.subject))
+                .or_else(client_ip),
+            RateLimitKey::ApiKey(header) => parts
+                .headers
+                .get(header)
+                .and_then(|x| x.to_str().ok())
+                .map(|x| format!("key:{}", hex::encode(Sha256::digest(x.as_bytes()))))
+                .or_else(client_ip),
+            RateLimitKey::Custom(key) => key(parts).map(|x| format!("custom:{x}")),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct RateLimitOptions {
+    pub store: Arc<dyn RateLimitStore>,
+    pub quota: Quota,
+    pub key: RateLimitKey,
+    ///Quotas for specific route templates, eg: `/auth/login`. Each has its own counter.
+    pub route_quotas: HashMap<String, Quota>,
+    ///Keeps counters of different layers sharing a store apart.
+    pub name: String,
+}
+
+impl RateLimitOptions {
+    pub fn new(store: impl RateLimitStore + 'static, quota: Quota) -> Self {
+        Self {
+            store: Arc::new(store),
+            quota,
+            key: RateLimitKey::ClientIp,
+            route_quotas: HashMap::new(),
+            name: String::from("default"),
+        }
+    }
+
+    pub fn with_key(mut self, key: RateLimitKey) -> Self {
+        self.key = key;
+        self
+    }
+
+    pub fn with_route_quota(mut self, route: impl Into<String>, quota: Quota) -> Self {
+        self.route_quotas.insert(route.into(), quota);
+        self
+    }
+
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+}
+
+fn ceil_secs(duration: Duration) -> u64 {
+    duration.as_secs() + u64::from(duration.subsec_nanos() > 0)
+}
+
+fn set_headers(response: &mut Response, quota: &Quota, decision: &RateLimitDecision) {
+    let headers = response.headers_mut();
+    headers.insert(RATE_LIMIT_LIMIT, decision.limit.into());
+    headers.insert(RATE_LIMIT_REMAINING, decision.remaining.into());
+    headers.insert(RATE_LIMIT_RESET, ceil_secs(decision.reset_after).into());
+    if let Ok(policy) =
+        HeaderValue::from_str(&format!("{};w={}", quota.limit, quota.window.as_secs()))
+    {
+        headers.insert(RATE_LIMIT_POLICY, policy);
+    }
+}
+
+///Counts the request and rejects it with 429 once the quota is used up.
+///The store failing lets the request through, so that an outage does not take the api down.
+pub async fn rate_limit_middleware(
+    req: Request<Body>,
+    next: Next,
+    options: RateLimitOptions,
+) -> Response {
+    let (parts, body) = req.into_parts();
+    let Some(key) = options.key.resolve(&parts) else {
+        return next.run(Request::from_parts(parts, body)).await;
+    };
+    let route = parts.extensions.get::<MatchedPath>().map(|x| x.as_str());
+    let (quota, key) = match route.and_then(|x| options.route_quotas.get(x).map(|q| (x, q))) {
+        Some((route, quota)) => (*quota, format!("{}:{}:{}", options.name, route, key)),
+        None => (options.quota, format!("{}:{}", options.name, key)),
+    };
+
+    let decision = match options.store.hit(&key, &quota).await {
+        Ok(decision) => decision,
+        Err(e) => {
+            tracing::warn!(error = ?e, "rate limit store failed, request allowed");
+            return next.run(Request::from_parts(parts, body)).await;
+        }
+    };
+
+    let mut response = if decision.allowed {
+        next.run(Request::from_parts(parts, body)).await
+    } else {
+        let retry_after = Duration::from_secs(ceil_secs(decision.reset_after));
+        Error::new_too_many_requests("Too many requests, try again later.", Some(retry_after))
+            .into_response()
+    };
+    set_headers(&mut response, &quota, &decision);
+    response
+}
+
+pub trait RateLimitLayer {
+    fn with_rate_limit_layer(self, options: RateLimitOptions) -> Self;
+}
+
+impl<T: Clone + Send + Sync + 'static> RateLimitLayer for Router<WebCoreState<T>> {
+    fn with_rate_limit_layer(self, options: RateLimitOptions) -> Self {
+        self.layer(middleware::from_fn(
+            move |req: Request<Body>, next: Next| {
+                let options = options.clone();
+                async move { rate_limit_middleware(req, next, options).await }
+            },
+        ))
+    }
+}
@@ -0,0 +1,139 @@
+use axum::http::{HeaderMap, HeaderName, header};
+use serde_json::Value;
+
+pub const REDACTED: &str = "[REDACTED]";
+
+///What gets hidden when headers and bodies are logged.
+#[derive(Debug, Clone)]
+pub struct Redaction {
+    ///Values of these headers are replaced with `[REDACTED]`.
+    pub headers: Vec<HeaderName>,
+    ///Json and form fields whose name contains one of these (case insensitive) are replaced
+    ///with `[REDACTED]`, eg: `token` also hides `access_token` and `refreshToken`.
+    pub fields: Vec<String>,
+}
+
+impl Default for Redaction {
+    fn default() -> Self {
+        Self {
+            headers: vec![
+                header::AUTHORIZATION,
+                header::PROXY_AUTHORIZATION,
+                header::COOKIE,
+                header::SET_COOKIE,
+                HeaderName::from_static("x-api-key"),
+            ],
+            fields: ["password", "token", "secret", "api_key", "apikey", "otp"]
+                .map(String::from)
+                .to_vec(),
+        }
+    }
+}
+
+impl Redaction {
+    pub fn with_header(mut self, header: HeaderName) -> Self {
+        self.headers.push(header);
+        self
+    }
+
+    pub fn with_field(mut self, field: impl Into<String>) -> Self {
+        self.fields.push(field.into().to_lowercase());
+        self
+    }
+
+    fn is_sensitive_field(&self, name: &str) -> bool {
+        let name = name.to_lowercase();
+        self.fields.iter().any(|x| name.contains(x.as_str()))
+    }
+
+    pub fn headers(&self, headers: &HeaderMap) -> String {
+        let mut map = serde_json::Map::new();
+        for (name, value) in headers {
+            let value = if self.headers.contains(name) {
+                REDACTED.to_string()
+            } else {
+                String::from_utf8_lossy(value.as_bytes()).into_owned()
+            };
+            map.insert(name.to_string(), Value::String(value));
+        }
+        Value::Object(map).to_string()
+    }
+
+    ///Json and form bodies are redacted field by field and cut at `max_bytes`. Any other body,
+    ///including json which does not parse, is only described, since its fields can not be
+    ///redacted.
+    pub fn body(&self, content_type: Option<&str>, body: &[u8], max_bytes: usize) -> String {
+        let content_type = content_type.unwrap_or_default();
+        let text = if content_type.contains("json") {
+            match serde_json::from_slice::<Value>(body) {
+                Ok(mut value) => {
+                    self.redact_json(&mut value);
+                    value.to_string()
+                }
+                Err(_) => return unparsed(content_type, body),
+            }
+        } else if content_type.starts_with("application/x-www-form-urlencoded") {
+            self.redact_form(&String::from_utf8_lossy(body))
+        } else {
+            return unparsed(content_type, body);
+        };
+        truncate(text, max_bytes)
+    }
+
+    fn redact_json(&self, value: &mut Value) {
+        match value {
+            Value::Object(map) => {
+                for (key, value) in map.iter_mut() {
+                    if self.is_sensitive_field(key) {
+                        *value = Value::String(REDACTED.to_string());
+                    } else {
+                        self.redact_json(value);
+                    }
+                }
+            }
+            Value::Array(values) => values.iter_mut().for_each(|x| self.redact_json(x)),
+            _ => {}
+        }
+    }
+
+    fn redact_form(&self, form: &str) -> String {
+        form.split('&')
+            .map(|pair| match pair.split_once('=') {
+                Some((key, _)) if self.is_sensitive_field(&decode_form_key(key)) => {
+                    format!("{key}={REDACTED}")
+                }
+                _ => pair.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join("&")
+    }
+}
+
+///`pass%77ord` and `pass+word` are matched as `password` and `pass word`.
+fn decode_form_key(key: &str) -> String {
+    url::form_urlencoded::parse(key.as_bytes())
+        .next()
+        .map(|(key, _)| key.into_owned())
+        .unwrap_or_default()
+}
+
+fn unparsed(content_type: &str, body: &[u8]) -> String {
+    if content_type.is_empty() {
+        return format!("<{} bytes, unparsed>", body.len());
+    }
+    format!("<{} bytes, unparsed {}>", body.len(), content_type)
+}
+
+fn truncate(mut text: String, max_bytes: usize) -> String {
+    if text.len() <= max_bytes {
+        return text;
+    }
+    let mut end = max_bytes;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    let total = text.len();
+    text.truncate(end);
+    text.push_str(&format!("...<{} more bytes>", total - end));
+    text
+}
@@ -0,0 +1,96 @@
+use axum::{
+    Router,
+    body::Body,
+    extract::FromRequestParts,
+    http::{HeaderValue, Request, request::Parts},
+    middleware::{self, Next},
+    response::Response,
+};
+
+use crate::{error::Error, web_core::WebCoreState};
+
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+const MAX_REQUEST_ID_LENGTH: usize = 128;
+
+tokio::task_local! {
+    static CURRENT_REQUEST_ID: RequestId;
+}
+
+///Id of the current request. Taken from `X-Request-Id` when the client sends a sane one,
+///generated otherwise. It is echoed in the `X-Request-Id` response header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestId(pub String);
+
+impl RequestId {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    fn from_header(value: &HeaderValue) -> Option<Self> {
+        let value = value.to_str().ok()?;
+        let is_valid = !value.is_empty()
+            && value.len() <= MAX_REQUEST_ID_LENGTH
+            && value
+                .chars()
+                .all(|x| x.is_ascii_alphanumeric() || matches!(x, '-' | '_' | '.' | ':'));
+        is_valid.then(|| Self(value.to_string()))
+    }
+
+    fn generate() -> Self {
+        Self(uuid::Uuid::new_v4().to_string())
+    }
+}
+
+impl std::fmt::Display for RequestId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+///Request id of the request being handled by the current task, if any.
+///Tasks spawned from a handler do not inherit it.
+pub fn current_request_id() -> Option<RequestId> {
+    CURRENT_REQUEST_ID.try_with(|x| x.clone()).ok()
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for RequestId {
+    type Rejection = Error;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts
+            .extensions
+            .get::<RequestId>()
+            .cloned()
+            .ok_or_else(|| Error::new_something_went_wrong("Request id layer is missing".into()))
+    }
+}
+
+pub async fn request_id_middleware(mut req: Request<Body>, next: Next) -> Response {
+    let request_id = req
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(RequestId::from_header)
+        .unwrap_or_else(RequestId::generate);
+    req.extensions_mut().insert(request_id.clone());
+
+    let mut response = CURRENT_REQUEST_ID
+        .scope(request_id.clone(), next.run(req))
+        .await;
+
+    if let Ok(value) = HeaderValue::from_str(request_id.as_str()) {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+    response.extensions_mut().insert(request_id);
+    response
+}
+
+pub trait RequestIdLayer {
+    fn with_request_id_layer(self) -> Self;
+}
+
+impl<T: Clone + Send + Sync + 'static> RequestIdLayer for Router<WebCoreState<T>> {
+    fn with_request_id_layer(self) -> Self {
+        self.layer(middleware::from_fn(request_id_middleware))
+    }
+}
@@ -0,0 +1,190 @@
+use std::{
+    pin::pin,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use axum::{
+    Router,
+    body::Body,
+    extract::DefaultBodyLimit,
+    http::Request,
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+};
+use tokio::{
+    sync::Semaphore,
+    time::{Instant, timeout_at},
+};
+
+use crate::{
+    error::{Error, code::CoreErrorCode},
+    web_core::WebCoreState,
+};
+
+///Status sent when a request takes longer than the timeout.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TimeoutStatus {
+    ///408 Request Timeout.
+    #[default]
+    RequestTimeout,
+    ///503 Service Unavailable, reported like other 5xx errors.
+    ServiceUnavailable,
+}
+
+impl TimeoutStatus {
+    fn error(self) -> Error {
+        let message = "Request took too long, try again later.";
+        match self {
+            TimeoutStatus::RequestTimeout => Error::new_request_timeout(message),
+            TimeoutStatus::ServiceUnavailable => Error::new_service_unavailable(message, None)
+                .with_code(CoreErrorCode::RequestTimeout),
+        }
+    }
+}
+
+///Limits applied to every request by `with_web_core`.
+///
+///Route groups can use different limits with `with_request_limits_layer`. The timeout and body
+///limit of the group replace the global ones, while the in-flight limit of the group applies on
+///top of the global one.
+#[derive(Debug, Clone)]
+pub struct RequestLimitsOptions {
+    ///Defaults to 30 seconds. `None` disables the timeout.
+    pub timeout: Option<Duration>,
+    pub timeout_status: TimeoutStatus,
+    ///Largest body accepted by the body extractors, larger bodies are rejected with 413.
+    ///Defaults to 2 MiB. `None` disables the limit.
+    pub max_body_bytes: Option<usize>,
+    ///Requests above this many in flight are shed with 503. Disabled by default.
+    pub max_in_flight: Option<Arc<Semaphore>>,
+}
+
+impl Default for RequestLimitsOptions {
+    fn default() -> Self {
+        Self {
+            timeout: Some(Duration::from_secs(30)),
+            timeout_status: TimeoutStatus::default(),
+            max_body_bytes: Some(2 * 1024 * 1024),
+            max_in_flight: None,
+        }
+    }
+}
+
+impl RequestLimitsOptions {
+    pub fn with_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn with_timeout_status(mut self, timeout_status: TimeoutStatus) -> Self {
+        self.timeout_status = timeout_status;
+        self
+    }
+
+    pub fn with_max_body_bytes(mut self, max_body_bytes: Option<usize>) -> Self {
+        self.max_body_bytes = max_body_bytes;
+        self
+    }
+
+    pub fn with_max_in_flight(mut self, max_in_flight: usize) -> Self {
+        self.max_in_flight = Some(Arc::new(Semaphore::new(max_in_flight)));
+        self
+    }
+}
+
+///Deadline of the request, shared so that route groups can replace the global timeout.
+#[derive(Clone)]
+struct RequestDeadline {
+    started_at: Instant,
+    current: Arc<Mutex<(Option<Instant>, TimeoutStatus)>>,
+}
+
+impl RequestDeadline {
+    fn new(options: &RequestLimitsOptions) -> Self {
+        let started_at = Instant::now();
+        let deadline = options.timeout.map(|x| started_at + x);
+        Self {
+            started_at,
+            current: Arc::new(Mutex::new((deadline, options.timeout_status))),
+        }
+    }
+
+    fn set(&self, options: &RequestLimitsOptions) {
+        *self.current.lock().unwrap_or_else(|e| e.into_inner()) = (
+            options.timeout.map(|x| self.started_at + x),
+            options.timeout_status,
+        );
+    }
+
+    fn get(&self) -> (Option<Instant>, TimeoutStatus) {
+        *self.current.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+fn overloaded() -> Response {
+    Error::new_service_unavailable(
+        "Server is busy, try again later.",
+        Some(Duration::from_secs(1)),
+    )
+    .with_code(CoreErrorCode::Overloaded)
+    .into_response()
+}
+
+///The outermost layer runs the request against the deadline, layers of route groups only
+///replace it.
+pub async fn request_limits_middleware(
+    mut req: Request<Body>,
+    next: Next,
+    options: RequestLimitsOptions,
+) -> Response {
+    let _permit = match &options.max_in_flight {
+        Some(semaphore) => match semaphore.clone().try_acquire_owned() {
+            Ok(permit) => Some(permit),
+            Err(_) => return overloaded(),
+        },
+        None => None,
+    };
+
+    if let Some(deadline) = req.extensions().get::<RequestDeadline>() {
+        deadline.set(&options);
+        return next.run(req).await;
+    }
+
+    let deadline = RequestDeadline::new(&options);
+    req.extensions_mut().insert(deadline.clone());
+    let mut response = pin!(next.run(req));
+    loop {
+        let (Some(at), _) = deadline.get() else {
+            return response.await;
+        };
+        if let Ok(response) = timeout_at(at, &mut response).await {
+            return response;
+        }
+        //The deadline may have been moved by a route group while waiting.
+        if let (Some(at), timeout_status) = deadline.get()
+            && at <= Instant::now()
+        {
+            return timeout_status.error().into_response();
+        }
+    }
+}
+
+pub trait RequestLimitsLayer {
+    fn with_request_limits_layer(self, options: RequestLimitsOptions) -> Self;
+}
+
+impl<T: Clone + Send + Sync + 'static> RequestLimitsLayer for Router<WebCoreState<T>> {
+    fn with_request_limits_layer(self, options: RequestLimitsOptions) -> Self {
+        let body_limit = match options.max_body_bytes {
+            Some(max_body_bytes) => DefaultBodyLimit::max(max_body_bytes),
+            None => DefaultBodyLimit::disable(),
+        };
+        self.layer(body_limit).layer(middleware::from_fn(
+            move |req: Request<Body>, next: Next| {
+                let options = options.clone();
+                async move { request_limits_middleware(req, next, options).await }
+            },
+        ))
+    }
+}
@@ -0,0 +1,244 @@
+use std::sync::{Arc, OnceLock};
+
+use axum::{
+    Router,
+    body::Body,
+    extract::FromRequestParts,
+    http::{HeaderName, HeaderValue, Request, header, request::Parts},
+    middleware::{self, Next},
+    response::{IntoResponseParts, Response, ResponseParts},
+};
+use base64::{Engine, prelude::BASE64_STANDARD};
+use rand_core::{OsRng, RngCore};
+
+use crate::{error::Error, web_core::WebCoreState};
+
+///Replaced with the nonce of the request in `content_security_policy`.
+pub const NONCE_PLACEHOLDER: &str = "{nonce}";
+
+///Headers added to every response which does not already set them. `None` leaves a header out.
+///
+///Routes can use a different policy by returning these options as part of the response, or by
+///adding `with_security_headers_override` to their router.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SecurityHeadersOptions {
+    pub strict_transport_security: Option<String>,
+    ///`{nonce}` is replaced with the `CspNonce` of the request,
+    ///eg: `script-src 'self' 'nonce-{nonce}'`.
+    pub content_security_policy: Option<String>,
+    pub content_type_options: Option<String>,
+    pub frame_options: Option<String>,
+    pub referrer_policy: Option<String>,
+    pub permissions_policy: Option<String>,
+}
+
+impl Default for SecurityHeadersOptions {
+    fn default() -> Self {
+        Self {
+            strict_transport_security: Some(String::from("max-age=31536000; includeSubDomains")),
+            content_security_policy: Some(String::from(
+                "default-src 'self'; object-src 'none'; base-uri 'self'; frame-ancestors 'none'",
+            )),
+            content_type_options: Some(String::from("nosniff")),
+            frame_options: Some(String::from("DENY")),
+            referrer_policy: Some(String::from("strict-origin-when-cross-origin")),
+            permissions_policy: Some(String::from("camera=(), microphone=(), geolocation=()")),
+        }
+    }
+}
+
+impl SecurityHeadersOptions {
+    ///No security headers at all.
+    pub fn none() -> Self {
+        Self {
+            strict_transport_security: None,
+            content_security_policy: None,
+            content_type_options: None,
+            frame_options: None,
+            referrer_policy: None,
+            permissions_policy: None,
+        }
+    }
+
+    pub fn with_strict_transport_security(mut self, value: Option<&str>) -> Self {
+        self.strict_transport_security = value.map(String::from);
+        self
+    }
+
+    pub fn with_content_security_policy(mut self, value: Option<&str>) -> Self {
+        self.content_security_policy = value.map(String::from);
+        self
+    }
+
+    pub fn with_content_type_options(mut self, value: Option<&str>) -> Self {
+        self.content_type_options = value.map(String::from);
+        self
+    }
+
+    pub fn with_frame_options(mut self, value: Option<&str>) -> Self {
+        self.frame_options = value.map(String::from);
+        self
+    }
+
+    pub fn with_referrer_policy(mut self, value: Option<&str>) -> Self {
+        self.referrer_policy = value.map(String::from);
+        self
+    }
+
+    pub fn with_permissions_policy(mut self, value: Option<&str>) -> Self {
+        self.permissions_policy = value.map(String::from);
+        self
+    }
+
+    fn uses_nonce(&self) -> bool {
+        self.content_security_policy
+            .as_deref()
+            .is_some_and(|x| x.contains(NONCE_PLACEHOLDER))
+    }
+
+    fn apply(&self, response: &mut Response, nonce: &NonceSlot) {
+        let content_security_policy = match &self.content_security_policy {
+            Some(policy) if self.uses_nonce() => {
+                Some(policy.replace(NONCE_PLACEHOLDER, &nonce.get().0))
+            }
+            policy => policy.clone(),
+        };
+        let headers = [
+            (
+                header::STRICT_TRANSPORT_SECURITY,
+                &self.strict_transport_security,
+            ),
+            (header::CONTENT_SECURITY_POLICY, &content_security_policy),
+            (header::X_CONTENT_TYPE_OPTIONS, &self.content_type_options),
+            (header::X_FRAME_OPTIONS, &self.frame_options),
+            (header::REFERRER_POLICY, &self.referrer_policy),
+            (
+                HeaderName::from_static("permissions-policy"),
+                &self.permissions_policy,
+            ),
+        ];
+        for (name, value) in headers {
+            let Some(value) = value else {
+                continue;
+            };
+            if response.headers().contains_key(&name) {
+                continue;
+            }
+            if let Ok(value) = HeaderValue::from_str(value) {
+                response.headers_mut().insert(name, value);
+            }
+        }
+    }
+}
+
+///Returning the options from a handler replaces the policy for that response.
+impl IntoResponseParts for SecurityHeadersOptions {
+    type Error = std::convert::Infallible;
+
+    fn into_response_parts(self, mut res: ResponseParts) -> Result<ResponseParts, Self::Error> {
+        res.extensions_mut().insert(self);
+        Ok(res)
+    }
+}
+
+///Random value generated per request when the applied content security policy contains
+///`{nonce}`, or when a handler extracts it.
+///Pass it to templates and use it as `<script nonce="{{ nonce }}">`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CspNonce(pub String);
+
+impl CspNonce {
+    fn generate() -> Self {
+        let mut bytes = [0u8; 16];
+        OsRng.fill_bytes(&mut bytes);
+        Self(BASE64_STANDARD.encode(bytes))
+    }
+}
+
+///Nonce of the request, generated the first time the handler or the applied policy needs it.
+///A policy overridden by a route or a response can use `{nonce}` even when the global one does not.
+#[derive(Debug, Clone, Default)]
+struct NonceSlot(Arc<OnceLock<CspNonce>>);
+
+impl NonceSlot {
+    fn get(&self) -> CspNonce {
+        self.0.get_or_init(CspNonce::generate).clone()
+    }
+}
+
+impl std::fmt::Display for CspNonce {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for CspNonce {
+    type Rejection = Error;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts
+            .extensions
+            .get::<NonceSlot>()
+            .map(|x| x.get())
+            .ok_or_else(|| {
+                Error::new_something_went_wrong(
+                    "Csp nonce is missing. Add `with_security_headers_layer`.".into(),
+                )
+            })
+    }
+}
+
+pub async fn security_headers_middleware(
+    mut req: Request<Body>,
+    next: Next,
+    options: SecurityHeadersOptions,
+) -> Response {
+    let nonce = NonceSlot::default();
+    req.extensions_mut().insert(nonce.clone());
+    let mut response = next.run(req).await;
+    match response
+        .extensions()
+        .get::<SecurityHeadersOptions>()
+        .cloned()
+    {
+        Some(route_options) => route_options.apply(&mut response, &nonce),
+        None => options.apply(&mut response, &nonce),
+    }
+    response
+}
+
+pub trait SecurityHeadersLayer {
+    fn with_security_headers_layer(self, options: SecurityHeadersOptions) -> Self;
+    ///Uses `options` instead of the policy given to `with_web_core` for the routes of this router.
+    fn with_security_headers_override(self, options: SecurityHeadersOptions) -> Self;
+}
+
+impl<T: Clone + Send + Sync + 'static> SecurityHeadersLayer for Router<WebCoreState<T>> {
+    fn with_security_headers_layer(self, options: SecurityHeadersOptions) -> Self {
+        self.layer(middleware::from_fn(
+            move |req: Request<Body>, next: Next| {
+                let options = options.clone();
+                async move { security_headers_middleware(req, next, options).await }
+            },
+        ))
+    }
+
+    fn with_security_headers_override(self, options: SecurityHeadersOptions) -> Self {
+        self.layer(middleware::from_fn(
+            move |req: Request<Body>, next: Next| {
+                let options = options.clone();
+                async move {
+                    let mut response = next.run(req).await;
+                    if response
+                        .extensions()
+                        .get::<SecurityHeadersOptions>()
+                        .is_none()
+                    {
+                        response.extensions_mut().insert(options);
+                    }
+                    response
+                }
+            },
+        ))
+    }
+}
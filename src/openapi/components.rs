@@ -0,0 +1,204 @@
+use std::collections::BTreeMap;
+
+use serde_json::{Value, json};
+
+use crate::error::{
+    authentication::AuthenticationError,
+    bad_request::BadRequestError,
+    code::CoreErrorCode,
+    field_validation::{FieldError, FieldValidationErrors},
+    not_found::NotFoundError,
+    something_went_wrong::SomethingWentWrong,
+};
+
+use super::{ApiSchema, json_content, schema_ref};
+
+///Name of the security scheme of `with_auth_layer`.
+pub const BEARER_SCHEME: &str = "bearer";
+
+pub const BAD_REQUEST_RESPONSE: &str = "BadRequest";
+pub const UNAUTHORIZED_RESPONSE: &str = "Unauthorized";
+pub const NOT_FOUND_RESPONSE: &str = "NotFound";
+pub const INTERNAL_ERROR_RESPONSE: &str = "InternalError";
+
+fn codes(codes: &[CoreErrorCode]) -> Value {
+    json!(codes.iter().map(|x| x.as_str()).collect::<Vec<_>>())
+}
+
+impl ApiSchema for FieldError {
+    fn schema_name() -> String {
+        String::from("FieldError")
+    }
+
+    fn schema() -> Value {
+        json!({
+            "type": "object",
+            "required": ["code", "message"],
+            "properties": {
+                "code": { "type": "string" },
+                "message": { "type": "string" },
+                "params": { "type": "object" },
+            },
+        })
+    }
+}
+
+impl ApiSchema for FieldValidationErrors {
+    fn schema_name() -> String {
+        String::from("FieldValidationErrors")
+    }
+
+    fn schema() -> Value {
+        let field_errors =
+            json!({ "type": "array", "items": schema_ref(&FieldError::schema_name()) });
+        json!({
+            "type": "object",
+            "required": ["error", "code", "fields"],
+            "properties": {
+                "error": { "type": "string" },
+                "code": { "type": "string", "examples": codes(&[CoreErrorCode::ValidationFailed]) },
+                "fields": {
+                    "description": "Errors of each field, keyed by path, eg: `address.zip`, `items[2].qty`.",
+                    "type": "object",
+                    "additionalProperties": field_errors,
+                },
+                "schema_errors": field_errors,
+            },
+        })
+    }
+}
+
+impl ApiSchema for BadRequestError {
+    fn schema_name() -> String {
+        String::from("BadRequestError")
+    }
+
+    fn schema() -> Value {
+        json!({
+            "type": "object",
+            "required": ["error", "code"],
+            "properties": {
+                "error": { "type": "string" },
+                "code": {
+                    "type": "string",
+                    "examples": codes(&[
+                        CoreErrorCode::BadRequest,
+                        CoreErrorCode::InvalidJson,
+                        CoreErrorCode::InvalidPath,
+                        CoreErrorCode::InvalidQuery,
+                        CoreErrorCode::MissingHeader,
+                        CoreErrorCode::InvalidHeader,
+                    ]),
+                },
+                "data": {},
+            },
+        })
+    }
+}
+
+impl ApiSchema for SomethingWentWrong {
+    fn schema_name() -> String {
+        String::from("SomethingWentWrong")
+    }
+
+    fn schema() -> Value {
+        json!({
+            "type": "object",
+            "required": ["error", "code", "error_id"],
+            "properties": {
+                "error": { "type": "string" },
+                "code": { "type": "string", "examples": codes(&[CoreErrorCode::InternalError]) },
+                "error_id": { "description": "Found in the error reports.", "type": "string" },
+                "request_id": { "description": "Found in the request logs.", "type": "string" },
+                "error_details": { "description": "Debug builds only.", "type": "string" },
+            },
+        })
+    }
+}
+
+impl ApiSchema for AuthenticationError {
+    fn schema_name() -> String {
+        String::from("AuthenticationError")
+    }
+
+    fn schema() -> Value {
+        json!({
+            "type": "object",
+            "required": ["error", "code"],
+            "properties": {
+                "error": { "type": "string" },
+                "code": {
+                    "type": "string",
+                    "examples": codes(&[
+                        CoreErrorCode::Unauthorized,
+                        CoreErrorCode::MissingToken,
+                        CoreErrorCode::TokenInvalid,
+                        CoreErrorCode::TokenExpired,
+                        CoreErrorCode::TokenPurposeMismatch,
+                    ]),
+                },
+                "error_details": { "description": "Debug builds only.", "type": "string" },
+            },
+        })
+    }
+}
+
+impl ApiSchema for NotFoundError {
+    fn schema_name() -> String {
+        String::from("NotFoundError")
+    }
+
+    fn schema() -> Value {
+        json!({
+            "type": "object",
+            "required": ["error", "code"],
+            "properties": {
+                "error": { "type": "string" },
+                "code": { "type": "string", "examples": codes(&[CoreErrorCode::NotFound]) },
+            },
+        })
+    }
+}
+
+fn component<S: ApiSchema>() -> (String, Value) {
+    (S::schema_name(), S::schema())
+}
+
+///Error bodies of the crate, in the `ErrorFormat::Json` shape.
+pub fn error_components() -> BTreeMap<String, Value> {
+    BTreeMap::from([
+        component::<FieldError>(),
+        component::<FieldValidationErrors>(),
+        component::<BadRequestError>(),
+        component::<SomethingWentWrong>(),
+        component::<AuthenticationError>(),
+        component::<NotFoundError>(),
+    ])
+}
+
+pub fn error_responses() -> Value {
+    let bad_request = json!({
+        "oneOf": [
+            schema_ref(&BadRequestError::schema_name()),
+            schema_ref(&FieldValidationErrors::schema_name()),
+        ],
+    });
+    json!({
+        BAD_REQUEST_RESPONSE: {
+            "description": "The request is malformed or fails validation.",
+            "content": json_content(bad_request),
+        },
+        UNAUTHORIZED_RESPONSE: {
+            "description": "The bearer token is missing, invalid or expired.",
+            "content": json_content(schema_ref(&AuthenticationError::schema_name())),
+        },
+        NOT_FOUND_RESPONSE: {
+            "description": "The resource does not exist.",
+            "content": json_content(schema_ref(&NotFoundError::schema_name())),
+        },
+        INTERNAL_ERROR_RESPONSE: {
+            "description": "Unexpected error, reported with its `error_id`.",
+            "content": json_content(schema_ref(&SomethingWentWrong::schema_name())),
+        },
+    })
+}
@@ -0,0 +1,355 @@
+pub mod components;
+pub mod router;
+pub mod ui;
+
+use std::collections::BTreeMap;
+
+use axum::http::{Method, StatusCode};
+use serde_json::{Map, Value, json};
+
+use components::{
+    BAD_REQUEST_RESPONSE, BEARER_SCHEME, INTERNAL_ERROR_RESPONSE, NOT_FOUND_RESPONSE,
+    UNAUTHORIZED_RESPONSE, error_components,
+};
+
+pub use router::ApiRouter;
+pub use ui::{OpenApiAsset, OpenApiAssets, OpenApiOptions, OpenApiRoutes, OpenApiUi};
+
+pub const OPENAPI_VERSION: &str = "3.1.0";
+
+///A type documented as a shared component, eg: a request or response body.
+pub trait ApiSchema {
+    ///Key in `components/schemas`.
+    fn schema_name() -> String;
+    ///JSON Schema (2020-12) of the type, it can `$ref` other components.
+    fn schema() -> Value;
+}
+
+fn schema_ref(name: &str) -> Value {
+    json!({ "$ref": format!("#/components/schemas/{name}") })
+}
+
+fn response_ref(name: &str) -> Value {
+    json!({ "$ref": format!("#/components/responses/{name}") })
+}
+
+fn json_content(schema: Value) -> Value {
+    json!({ "application/json": { "schema": schema } })
+}
+
+///`{id}` segments of an axum path.
+fn path_parameter_names(path: &str) -> Vec<String> {
+    path.split('/')
+        .filter_map(|x| x.strip_prefix('{')?.strip_suffix('}'))
+        .map(|x| x.trim_start_matches('*').to_string())
+        .collect()
+}
+
+///Documentation of a single route, `path` uses the axum syntax, eg: `/posts/{id}`.
+///
+///Error responses of the crate are added when the operation does not document them itself:
+///500 for every operation, 400 when it has a body or parameters, 401 when it is behind
+///`with_auth_layer` and 404 when its path has parameters.
+#[derive(Debug, Clone)]
+pub struct Operation {
+    pub method: Method,
+    pub path: String,
+    pub summary: Option<String>,
+    pub description: Option<String>,
+    pub operation_id: Option<String>,
+    pub tags: Vec<String>,
+    pub parameters: Vec<Value>,
+    pub request_body: Option<Value>,
+    pub responses: BTreeMap<u16, Value>,
+    ///Sends the bearer token of `with_auth_layer`.
+    pub authenticated: bool,
+    ///Components referenced by the operation.
+    pub schemas: BTreeMap<String, Value>,
+}
+
+impl Operation {
+    pub fn new(method: Method, path: impl Into<String>) -> Self {
+        Self {
+            method,
+            path: path.into(),
+            summary: None,
+            description: None,
+            operation_id: None,
+            tags: vec![],
+            parameters: vec![],
+            request_body: None,
+            responses: BTreeMap::new(),
+            authenticated: false,
+            schemas: BTreeMap::new(),
+        }
+    }
+
+    pub fn get(path: impl Into<String>) -> Self {
+        Self::new(Method::GET, path)
+    }
+
+    pub fn post(path: impl Into<String>) -> Self {
+        Self::new(Method::POST, path)
+    }
+
+    pub fn put(path: impl Into<String>) -> Self {
+        Self::new(Method::PUT, path)
+    }
+
+    pub fn patch(path: impl Into<String>) -> Self {
+        Self::new(Method::PATCH, path)
+    }
+
+    pub fn delete(path: impl Into<String>) -> Self {
+        Self::new(Method::DELETE, path)
+    }
+
+    pub fn with_summary(mut self, summary: impl Into<String>) -> Self {
+        self.summary = Some(summary.into());
+        self
+    }
+
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    pub fn with_operation_id(mut self, operation_id: impl Into<String>) -> Self {
+        self.operation_id = Some(operation_id.into());
+        self
+    }
+
+    pub fn with_tag(mut self, tag: impl Into<String>) -> Self {
+        self.tags.push(tag.into());
+        self
+    }
+
+    ///Path parameters are documented as strings unless they are given here.
+    pub fn with_path_parameter(self, name: &str, schema: Value) -> Self {
+        self.with_parameter(name, "path", true, schema)
+    }
+
+    pub fn with_query_parameter(self, name: &str, required: bool, schema: Value) -> Self {
+        self.with_parameter(name, "query", required, schema)
+    }
+
+    pub fn with_header_parameter(self, name: &str, required: bool, schema: Value) -> Self {
+        self.with_parameter(name, "header", required, schema)
+    }
+
+    fn with_parameter(mut self, name: &str, location: &str, required: bool, schema: Value) -> Self {
+        self.parameters.push(json!({
+            "name": name,
+            "in": location,
+            "required": required,
+            "schema": schema,
+        }));
+        self
+    }
+
+    ///Json body of type `B`, added to the components.
+    pub fn with_request_body<B: ApiSchema>(self) -> Self {
+        self.with_json_request_body(&B::schema_name(), B::schema())
+    }
+
+    pub fn with_json_request_body(mut self, name: &str, schema: Value) -> Self {
+        self.schemas.insert(name.to_string(), schema);
+        self.request_body = Some(json!({
+            "required": true,
+            "content": json_content(schema_ref(name)),
+        }));
+        self
+    }
+
+    ///Json response of type `R`, added to the components.
+    pub fn with_response<R: ApiSchema>(self, status: StatusCode, description: &str) -> Self {
+        self.with_json_response(status, description, &R::schema_name(), R::schema())
+    }
+
+    pub fn with_json_response(
+        mut self,
+        status: StatusCode,
+        description: &str,
+        name: &str,
+        schema: Value,
+    ) -> Self {
+        self.schemas.insert(name.to_string(), schema);
+        self.responses.insert(
+            status.as_u16(),
+            json!({
+                "description": description,
+                "content": json_content(schema_ref(name)),
+            }),
+        );
+        self
+    }
+
+    ///Response without a body, eg: `204 No Content`.
+    pub fn with_empty_response(mut self, status: StatusCode, description: &str) -> Self {
+        self.responses
+            .insert(status.as_u16(), json!({ "description": description }));
+        self
+    }
+
+    pub fn with_bearer_auth(mut self) -> Self {
+        self.authenticated = true;
+        self
+    }
+
+    fn to_value(&self) -> Value {
+        let mut operation = Map::new();
+        if let Some(summary) = &self.summary {
+            operation.insert("summary".into(), json!(summary));
+        }
+        if let Some(description) = &self.description {
+            operation.insert("description".into(), json!(description));
+        }
+        if let Some(operation_id) = &self.operation_id {
+            operation.insert("operationId".into(), json!(operation_id));
+        }
+        if !self.tags.is_empty() {
+            operation.insert("tags".into(), json!(self.tags));
+        }
+
+        let mut parameters = self.parameters.clone();
+        let path_parameters = path_parameter_names(&self.path);
+        for name in &path_parameters {
+            let documented = self
+                .parameters
+                .iter()
+                .any(|x| x["in"] == "path" && x["name"] == name.as_str());
+            if !documented {
+                parameters.push(json!({
+                    "name": name,
+                    "in": "path",
+                    "required": true,
+                    "schema": { "type": "string" },
+                }));
+            }
+        }
+        if !parameters.is_empty() {
+            operation.insert("parameters".into(), json!(parameters));
+        }
+        if let Some(request_body) = &self.request_body {
+            operation.insert("requestBody".into(), request_body.clone());
+        }
+
+        let mut responses: BTreeMap<String, Value> = self
+            .responses
+            .iter()
+            .map(|(status, response)| (status.to_string(), response.clone()))
+            .collect();
+        let mut default_response = |status: StatusCode, name: &str| {
+            responses
+                .entry(status.as_u16().to_string())
+                .or_insert_with(|| response_ref(name));
+        };
+        if self.request_body.is_some() || !parameters.is_empty() {
+            default_response(StatusCode::BAD_REQUEST, BAD_REQUEST_RESPONSE);
+        }
+        if self.authenticated {
+            default_response(StatusCode::UNAUTHORIZED, UNAUTHORIZED_RESPONSE);
+        }
+        if !path_parameters.is_empty() {
+            default_response(StatusCode::NOT_FOUND, NOT_FOUND_RESPONSE);
+        }
+        default_response(StatusCode::INTERNAL_SERVER_ERROR, INTERNAL_ERROR_RESPONSE);
+        operation.insert("responses".into(), json!(responses));
+
+        if self.authenticated {
+            operation.insert("security".into(), json!([{ BEARER_SCHEME: [] }]));
+        }
+        Value::Object(operation)
+    }
+}
+
+///OpenAPI 3.1 document of an api, built from `Operation`s or from the routes of an `ApiRouter`.
+#[derive(Debug, Clone)]
+pub struct OpenApi {
+    pub title: String,
+    pub version: String,
+    pub description: Option<String>,
+    pub servers: Vec<String>,
+    pub operations: Vec<Operation>,
+    pub schemas: BTreeMap<String, Value>,
+}
+
+impl OpenApi {
+    pub fn new(title: impl Into<String>, version: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            version: version.into(),
+            description: None,
+            servers: vec![],
+            operations: vec![],
+            schemas: BTreeMap::new(),
+        }
+    }
+
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    ///eg: `https://api.example.com/prod`.
+    pub fn with_server(mut self, url: impl Into<String>) -> Self {
+        self.servers.push(url.into());
+        self
+    }
+
+    pub fn with_operation(mut self, operation: Operation) -> Self {
+        self.operations.push(operation);
+        self
+    }
+
+    ///Operations registered on an `ApiRouter`, see `ApiRouter::into_parts`.
+    pub fn with_operations(mut self, operations: Vec<Operation>) -> Self {
+        self.operations.extend(operations);
+        self
+    }
+
+    ///Adds a component which no operation references directly.
+    pub fn with_schema<S: ApiSchema>(mut self) -> Self {
+        self.schemas.insert(S::schema_name(), S::schema());
+        self
+    }
+
+    pub fn document(&self) -> Value {
+        let mut paths: BTreeMap<&str, Map<String, Value>> = BTreeMap::new();
+        let mut schemas = error_components();
+        for operation in &self.operations {
+            paths.entry(&operation.path).or_default().insert(
+                operation.method.as_str().to_lowercase(),
+                operation.to_value(),
+            );
+            schemas.extend(operation.schemas.clone());
+        }
+        schemas.extend(self.schemas.clone());
+
+        let mut info = json!({ "title": self.title, "version": self.version });
+        if let Some(description) = &self.description {
+            info["description"] = json!(description);
+        }
+        let mut document = json!({
+            "openapi": OPENAPI_VERSION,
+            "info": info,
+            "paths": paths,
+            "components": {
+                "schemas": schemas,
+                "responses": components::error_responses(),
+                "securitySchemes": {
+                    BEARER_SCHEME: {
+                        "type": "http",
+                        "scheme": "bearer",
+                        "bearerFormat": "JWT",
+                    },
+                },
+            },
+        });
+        if !self.servers.is_empty() {
+            let servers: Vec<Value> = self.servers.iter().map(|x| json!({ "url": x })).collect();
+            document["servers"] = json!(servers);
+        }
+        document
+    }
+}
@@ -0,0 +1,108 @@
+use std::sync::Arc;
+
+use axum::{
+    Router,
+    handler::Handler,
+    routing::{MethodFilter, on},
+};
+
+use crate::{
+    auth::{auth_service::AuthService, authentication_middleware::AuthMiddlewareLayer},
+    web_core::WebCoreState,
+};
+
+use super::Operation;
+
+///Router which documents each route as it is mounted, so that the document follows the routes.
+pub struct ApiRouter<T>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    router: Router<WebCoreState<T>>,
+    operations: Vec<Operation>,
+}
+
+impl<T> Default for ApiRouter<T>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    fn default() -> Self {
+        Self {
+            router: Router::new(),
+            operations: vec![],
+        }
+    }
+}
+
+impl<T> ApiRouter<T>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    ///Mounts `handler` on the method and path of `operation`, so the document can not disagree
+    ///with the route. Operations of the same path are merged, like with `Router::route`.
+    ///
+    ///Panics when the method of `operation` is not one axum can route, eg: a custom method.
+    pub fn route<H, X>(mut self, operation: Operation, handler: H) -> Self
+    where
+        H: Handler<X, WebCoreState<T>>,
+        X: 'static,
+    {
+        let filter = MethodFilter::try_from(operation.method.clone())
+            .unwrap_or_else(|_| panic!("Can not route the {} method", operation.method));
+        self.router = self.router.route(&operation.path, on(filter, handler));
+        self.operations.push(operation);
+        self
+    }
+
+    pub fn merge(mut self, other: ApiRouter<T>) -> Self {
+        self.router = self.router.merge(other.router);
+        self.operations.extend(other.operations);
+        self
+    }
+
+    ///Operations paths are prefixed with `path`.
+    pub fn nest(mut self, path: &str, other: ApiRouter<T>) -> Self {
+        self.router = self.router.nest(path, other.router);
+        self.operations
+            .extend(other.operations.into_iter().map(|mut operation| {
+                operation.path = match operation.path.as_str() {
+                    "/" => path.to_string(),
+                    _ => format!("{path}{}", operation.path),
+                };
+                operation
+            }));
+        self
+    }
+
+    ///Applies layers which do not change the documentation, eg: `with_rate_limit_layer`.
+    pub fn map_router(
+        mut self,
+        f: impl FnOnce(Router<WebCoreState<T>>) -> Router<WebCoreState<T>>,
+    ) -> Self {
+        self.router = f(self.router);
+        self
+    }
+
+    ///Pass the operations to `OpenApi::with_operations`.
+    pub fn into_parts(self) -> (Router<WebCoreState<T>>, Vec<Operation>) {
+        (self.router, self.operations)
+    }
+}
+
+///Operations mounted so far are documented with the bearer security scheme.
+impl<T> AuthMiddlewareLayer for ApiRouter<T>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    fn with_auth_layer(mut self, auth_service: Arc<AuthService>) -> Self {
+        self.router = self.router.with_auth_layer(auth_service);
+        for operation in &mut self.operations {
+            operation.authenticated = true;
+        }
+        self
+    }
+}
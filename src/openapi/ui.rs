@@ -0,0 +1,266 @@
+use axum::{
+    Json, Router,
+    response::{Html, IntoResponse},
+    routing::get,
+};
+use base64::{Engine, prelude::BASE64_STANDARD};
+use sha2::{Digest, Sha256};
+
+use crate::{middleware::security_headers::SecurityHeadersOptions, web_core::WebCoreState};
+
+use super::OpenApi;
+
+pub const OPENAPI_PATH: &str = "/openapi.json";
+pub const DOCS_PATH: &str = "/docs";
+
+pub const SWAGGER_UI_VERSION: &str = "5.17.14";
+pub const REDOC_VERSION: &str = "2.1.5";
+
+///Page rendering the document. Assets are loaded from a CDN, at a pinned version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OpenApiUi {
+    SwaggerUi,
+    Redoc,
+}
+
+impl OpenApiUi {
+    ///Assets of the pinned version on unpkg, without integrity.
+    pub fn default_assets(self) -> OpenApiAssets {
+        match self {
+            OpenApiUi::SwaggerUi => OpenApiAssets {
+                script: OpenApiAsset::new(format!(
+                    "https://unpkg.com/swagger-ui-dist@{SWAGGER_UI_VERSION}/swagger-ui-bundle.js"
+                )),
+                stylesheet: Some(OpenApiAsset::new(format!(
+                    "https://unpkg.com/swagger-ui-dist@{SWAGGER_UI_VERSION}/swagger-ui.css"
+                ))),
+            },
+            OpenApiUi::Redoc => OpenApiAssets {
+                script: OpenApiAsset::new(format!(
+                    "https://unpkg.com/redoc@{REDOC_VERSION}/bundles/redoc.standalone.js"
+                )),
+                stylesheet: None,
+            },
+        }
+    }
+}
+
+///File loaded by the documentation page. Only its exact url is allowed by the page's CSP.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OpenApiAsset {
+    pub url: String,
+    ///Subresource integrity of the file, eg: `sha384-...`. The browser refuses the file when
+    ///it does not match.
+    pub integrity: Option<String>,
+}
+
+impl OpenApiAsset {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            integrity: None,
+        }
+    }
+
+    pub fn with_integrity(mut self, integrity: impl Into<String>) -> Self {
+        self.integrity = Some(integrity.into());
+        self
+    }
+
+    ///Relative urls are already allowed by `'self'`.
+    fn csp_source(&self) -> &str {
+        match self.url.starts_with("https://") || self.url.starts_with("http://") {
+            true => &self.url,
+            false => "",
+        }
+    }
+
+    fn attributes(&self, url_attribute: &str) -> String {
+        let url = escape_html(&self.url);
+        match &self.integrity {
+            Some(integrity) => format!(
+                r#"{url_attribute}="{url}" integrity="{}" crossorigin="anonymous""#,
+                escape_html(integrity)
+            ),
+            None => format!(r#"{url_attribute}="{url}""#),
+        }
+    }
+}
+
+///Script and stylesheet of the documentation page.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OpenApiAssets {
+    pub script: OpenApiAsset,
+    ///Redoc has none, it styles itself.
+    pub stylesheet: Option<OpenApiAsset>,
+}
+
+#[derive(Debug, Clone)]
+pub struct OpenApiOptions {
+    ///Defaults to `/openapi.json`.
+    pub path: String,
+    ///Path and kind of the documentation page. No page is mounted by default.
+    pub ui: Option<(String, OpenApiUi)>,
+    ///Used instead of `OpenApiUi::default_assets`, eg: to add integrity or to self host them.
+    pub assets: Option<OpenApiAssets>,
+}
+
+impl Default for OpenApiOptions {
+    fn default() -> Self {
+        Self {
+            path: OPENAPI_PATH.to_string(),
+            ui: None,
+            assets: None,
+        }
+    }
+}
+
+impl OpenApiOptions {
+    pub fn with_path(mut self, path: impl Into<String>) -> Self {
+        self.path = path.into();
+        self
+    }
+
+    ///Mounts Swagger UI on `/docs`.
+    pub fn with_swagger_ui(self) -> Self {
+        self.with_ui(DOCS_PATH, OpenApiUi::SwaggerUi)
+    }
+
+    ///Mounts Redoc on `/docs`.
+    pub fn with_redoc(self) -> Self {
+        self.with_ui(DOCS_PATH, OpenApiUi::Redoc)
+    }
+
+    pub fn with_ui(mut self, path: impl Into<String>, ui: OpenApiUi) -> Self {
+        self.ui = Some((path.into(), ui));
+        self
+    }
+
+    pub fn with_assets(mut self, assets: OpenApiAssets) -> Self {
+        self.assets = Some(assets);
+        self
+    }
+}
+
+fn escape_html(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+///Url of `to` relative to the page at `from`, so the page keeps working behind a stage prefix.
+fn relative_url(from: &str, to: &str) -> String {
+    let depth = from.trim_start_matches('/').matches('/').count();
+    format!("{}{}", "../".repeat(depth), to.trim_start_matches('/'))
+}
+
+///Page and the content security policy it needs, which allows the exact asset urls and the
+///inline script.
+fn ui_page(ui: OpenApiUi, assets: &OpenApiAssets, title: &str, spec_url: &str) -> (String, String) {
+    let title = escape_html(title);
+    let script = assets.script.attributes("src");
+    let stylesheet = match &assets.stylesheet {
+        Some(stylesheet) => format!(
+            "\n<link rel=\"stylesheet\" {}>",
+            stylesheet.attributes("href")
+        ),
+        None => String::new(),
+    };
+    let style_src = match &assets.stylesheet {
+        Some(stylesheet) => format!("'self' 'unsafe-inline' {}", stylesheet.csp_source()),
+        None => String::from("'self' 'unsafe-inline'"),
+    };
+    match ui {
+        OpenApiUi::SwaggerUi => {
+            let inline_script = format!(
+                "window.ui = SwaggerUIBundle({{ url: {}, dom_id: '#swagger-ui' }});",
+                serde_json::to_string(spec_url).unwrap_or_default()
+            );
+            let script_hash = BASE64_STANDARD.encode(Sha256::digest(inline_script.as_bytes()));
+            let page = format!(
+                r#"<!doctype html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>{title}</title>{stylesheet}
+</head>
+<body>
+<div id="swagger-ui"></div>
+<script {script}></script>
+<script>{inline_script}</script>
+</body>
+</html>"#
+            );
+            let csp = format!(
+                "default-src 'self'; script-src 'self' {} 'sha256-{script_hash}'; \
+                 style-src {style_src}; img-src 'self' data:; object-src 'none'",
+                assets.script.csp_source()
+            );
+            (page, csp)
+        }
+        OpenApiUi::Redoc => {
+            let page = format!(
+                r#"<!doctype html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>{title}</title>{stylesheet}
+</head>
+<body>
+<redoc spec-url="{}"></redoc>
+<script {script}></script>
+</body>
+</html>"#,
+                escape_html(spec_url)
+            );
+            let csp = format!(
+                "default-src 'self'; script-src 'self' {}; \
+                 style-src {style_src} https://fonts.googleapis.com; \
+                 font-src https://fonts.gstatic.com; img-src 'self' data:; \
+                 worker-src blob:; object-src 'none'",
+                assets.script.csp_source()
+            );
+            (page, csp)
+        }
+    }
+}
+
+pub trait OpenApiRoutes {
+    ///Serves the document of `api` as json, and the documentation page when configured.
+    fn with_openapi_routes(self, api: OpenApi, options: OpenApiOptions) -> Self;
+}
+
+impl<T> OpenApiRoutes for Router<WebCoreState<T>>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    fn with_openapi_routes(self, api: OpenApi, options: OpenApiOptions) -> Self {
+        let document = api.document();
+        let router = self.route(
+            &options.path,
+            get(move || {
+                let document = document.clone();
+                async move { Json(document) }
+            }),
+        );
+        match options.ui {
+            Some((ui_path, ui)) => {
+                let assets = options.assets.unwrap_or_else(|| ui.default_assets());
+                let spec_url = relative_url(&ui_path, &options.path);
+                let (page, csp) = ui_page(ui, &assets, &api.title, &spec_url);
+                let security_headers =
+                    SecurityHeadersOptions::default().with_content_security_policy(Some(&csp));
+                router.route(
+                    &ui_path,
+                    get(move || {
+                        let response = (security_headers.clone(), Html(page.clone()));
+                        async move { response.into_response() }
+                    }),
+                )
+            }
+            None => router,
+        }
+    }
+}
@@ -1,10 +1,27 @@
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use reqwest::Client;
 use serde_json::Value;
 use url::Url;
 
+use crate::metrics::metrics;
+
+///Latency is recorded as `outbound_request_duration_seconds{host="<url host>"}`.
 pub async fn post_api<R>(url: &Url, url_path: &str, json_data: impl Into<Value>) -> Result<R, ()>
+where
+    R: serde::de::DeserializeOwned,
+{
+    let started_at = Instant::now();
+    let result = call_api(url, url_path, json_data).await;
+    metrics().record_outbound(
+        url.host_str().unwrap_or("unknown"),
+        result.is_ok(),
+        started_at.elapsed(),
+    );
+    result
+}
+
+async fn call_api<R>(url: &Url, url_path: &str, json_data: impl Into<Value>) -> Result<R, ()>
 where
     R: serde::de::DeserializeOwned,
 {
@@ -41,11 +58,11 @@ where
         return Err(());
     }
 
-    return match response.json().await {
+    match response.json().await {
         Ok(x) => Ok(x),
         Err(e) => {
             eprintln!("Error while deserialising api resonse : {}", e);
             Err(())
-        },
-    };
+        }
+    }
 }
@@ -0,0 +1,310 @@
+use std::{collections::HashSet, fmt::Display, sync::Arc};
+
+use axum::{Router, routing::MethodRouter};
+
+use crate::{
+    auth::{
+        auth_service::AuthService, authentication_middleware::authenticate, roles::require_role,
+    },
+    cors::{CorsError, CorsOptions},
+    middleware::{
+        headers::ensure_header_value,
+        middleware_handler::crate_middleware_handler,
+        rate_limit::{Quota, RateLimitOptions, rate_limit_middleware},
+    },
+    web_core::WebCoreState,
+};
+
+///Role required by `RouteGroup::admin`.
+pub const ADMIN_ROLE: &str = "admin";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum_macros::Display)]
+#[strum(serialize_all = "snake_case")]
+pub enum RouteGroupKind {
+    Public,
+    Authenticated,
+    Admin,
+    Internal,
+}
+
+///Routes sharing the same protections. The stack runs, from the outside in: CORS, required
+///headers, authentication, role and rate limit.
+pub struct RouteGroup<T>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    kind: RouteGroupKind,
+    router: Router<WebCoreState<T>>,
+    paths: Vec<String>,
+    authenticated: bool,
+    role: Option<String>,
+    required_headers: Vec<(String, String)>,
+    rate_limit: Option<RateLimitOptions>,
+    cors: Option<CorsOptions>,
+}
+
+impl<T> RouteGroup<T>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    fn new(kind: RouteGroupKind) -> Self {
+        Self {
+            kind,
+            router: Router::new(),
+            paths: vec![],
+            authenticated: false,
+            role: None,
+            required_headers: vec![],
+            rate_limit: None,
+            cors: None,
+        }
+    }
+
+    pub fn public() -> Self {
+        Self::new(RouteGroupKind::Public)
+    }
+
+    ///Requires a valid access token.
+    pub fn authenticated() -> Self {
+        Self {
+            authenticated: true,
+            ..Self::new(RouteGroupKind::Authenticated)
+        }
+    }
+
+    ///Requires a valid access token with the `admin` role, see `with_role` to use another one.
+    pub fn admin() -> Self {
+        Self {
+            authenticated: true,
+            role: Some(ADMIN_ROLE.to_string()),
+            ..Self::new(RouteGroupKind::Admin)
+        }
+    }
+
+    ///Service to service routes, which require the `key` header to be `value`.
+    ///Browsers are refused, as the group allows no CORS origin unless `with_cors` is used.
+    pub fn internal(key: &str, value: &str) -> Self {
+        Self {
+            required_headers: vec![(key.to_string(), value.to_string())],
+            cors: Some(CorsOptions::default()),
+            ..Self::new(RouteGroupKind::Internal)
+        }
+    }
+
+    pub fn route(mut self, path: &str, method_router: MethodRouter<WebCoreState<T>>) -> Self {
+        self.router = self.router.route(path, method_router);
+        self.paths.push(path.to_string());
+        self
+    }
+
+    ///Requires a valid access token with `role` in its `roles` claim.
+    pub fn with_role(mut self, role: &str) -> Self {
+        self.authenticated = true;
+        self.role = Some(role.to_string());
+        self
+    }
+
+    ///Answers 403 unless the `key` header is `value`. Can be called multiple times.
+    pub fn with_required_header(mut self, key: &str, value: &str) -> Self {
+        self.required_headers
+            .push((key.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn with_rate_limit(mut self, rate_limit: RateLimitOptions) -> Self {
+        self.rate_limit = Some(rate_limit);
+        self
+    }
+
+    ///Used instead of the CORS options of `WebCoreOptions` for the routes of the group.
+    pub fn with_cors(mut self, cors: CorsOptions) -> Self {
+        self.cors = Some(cors);
+        self
+    }
+
+    fn rows(&self) -> impl Iterator<Item = RouteTableRow> + '_ {
+        self.paths.iter().map(|path| RouteTableRow {
+            path: path.clone(),
+            group: self.kind,
+            authenticated: self.authenticated,
+            role: self.role.clone(),
+            required_headers: self
+                .required_headers
+                .iter()
+                .map(|(key, _)| key.clone())
+                .collect(),
+            rate_limit: self.rate_limit.as_ref().map(|x| x.quota),
+            own_cors: self.cors.is_some(),
+        })
+    }
+
+    ///Layers only wrap the routes of the group, so that unknown paths keep answering 404
+    ///rather than going through the stack of whichever group was merged last.
+    fn build(self, auth_service: &Arc<AuthService>) -> Result<Router<WebCoreState<T>>, CorsError> {
+        let mut router = self.router;
+        //`route_layer` panics on a router without routes.
+        if self.paths.is_empty() {
+            return Ok(router);
+        }
+        if let Some(rate_limit) = self.rate_limit {
+            router = router.route_layer(crate_middleware_handler(move |req, next| {
+                rate_limit_middleware(req, next, rate_limit.clone())
+            }));
+        }
+        if let Some(role) = self.role {
+            let auth_service = auth_service.clone();
+            let role: Arc<str> = Arc::from(role);
+            router = router.route_layer(crate_middleware_handler(move |req, next| {
+                require_role(req, next, auth_service.clone(), role.clone())
+            }));
+        }
+        if self.authenticated {
+            let auth_service = auth_service.clone();
+            router = router.route_layer(crate_middleware_handler(move |req, next| {
+                authenticate(req, next, auth_service.clone())
+            }));
+        }
+        for (key, value) in self.required_headers {
+            let key: Arc<str> = Arc::from(key);
+            let value: Arc<str> = Arc::from(value);
+            router = router.route_layer(crate_middleware_handler(move |req, next| {
+                ensure_header_value(req, next, key.clone(), value.clone())
+            }));
+        }
+        if let Some(cors) = self.cors {
+            router = router.route_layer(cors.build()?);
+        }
+        Ok(router)
+    }
+}
+
+///Groups mounted by `WebCoreOptions::with_route_groups`. Their route table is logged when the
+///router is built.
+pub struct RouteGroups<T>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    groups: Vec<RouteGroup<T>>,
+}
+
+impl<T> Default for RouteGroups<T>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    fn default() -> Self {
+        Self { groups: vec![] }
+    }
+}
+
+impl<T> RouteGroups<T>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_group(mut self, group: RouteGroup<T>) -> Self {
+        self.groups.push(group);
+        self
+    }
+
+    pub fn route_table(&self) -> RouteTable {
+        let mut rows: Vec<RouteTableRow> = self.groups.iter().flat_map(|x| x.rows()).collect();
+        rows.sort_by(|a, b| a.path.cmp(&b.path));
+        RouteTable { rows }
+    }
+
+    ///Router of every group, and the routes which answer CORS themselves.
+    pub(crate) fn build(
+        self,
+        auth_service: &Arc<AuthService>,
+    ) -> Result<(Router<WebCoreState<T>>, HashSet<String>), CorsError> {
+        let mut router = Router::new();
+        let mut own_cors = HashSet::new();
+        for group in self.groups {
+            if group.cors.is_some() {
+                own_cors.extend(group.paths.iter().cloned());
+            }
+            router = router.merge(group.build(auth_service)?);
+        }
+        Ok((router, own_cors))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RouteTableRow {
+    pub path: String,
+    pub group: RouteGroupKind,
+    pub authenticated: bool,
+    pub role: Option<String>,
+    ///Names only, values are often secrets.
+    pub required_headers: Vec<String>,
+    pub rate_limit: Option<Quota>,
+    ///`false` when the route uses the CORS options of `WebCoreOptions`.
+    pub own_cors: bool,
+}
+
+impl RouteTableRow {
+    fn cells(&self) -> [String; 7] {
+        let or_dash = |x: Option<String>| x.unwrap_or_else(|| String::from("-"));
+        [
+            self.path.clone(),
+            self.group.to_string(),
+            String::from(match self.authenticated {
+                true => "bearer",
+                false => "-",
+            }),
+            or_dash(self.role.clone()),
+            or_dash((!self.required_headers.is_empty()).then(|| self.required_headers.join(","))),
+            or_dash(
+                self.rate_limit
+                    .map(|x| format!("{}/{}s", x.limit, x.window.as_secs())),
+            ),
+            String::from(match self.own_cors {
+                true => "group",
+                false => "global",
+            }),
+        ]
+    }
+}
+
+///Protections of each route, eg: printed at startup to spot a route mounted in the wrong group.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RouteTable {
+    pub rows: Vec<RouteTableRow>,
+}
+
+impl Display for RouteTable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let header = [
+            "PATH",
+            "GROUP",
+            "AUTH",
+            "ROLE",
+            "HEADERS",
+            "RATE LIMIT",
+            "CORS",
+        ]
+        .map(String::from);
+        let rows: Vec<[String; 7]> = std::iter::once(header)
+            .chain(self.rows.iter().map(|x| x.cells()))
+            .collect();
+        let mut widths = [0; 7];
+        for row in &rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.len());
+            }
+        }
+        for row in &rows {
+            let line = row
+                .iter()
+                .zip(widths)
+                .map(|(cell, width)| format!("{cell:width$}"))
+                .collect::<Vec<_>>()
+                .join("  ");
+            writeln!(f, "{}", line.trim_end())?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,264 @@
+use std::{
+    future::IntoFuture,
+    net::{Ipv4Addr, SocketAddr},
+    pin::pin,
+    time::Duration,
+};
+
+use axum::{
+    Router,
+    extract::Request,
+    http::{Uri, uri::PathAndQuery},
+};
+use futures::future::{Either, select};
+use lambda_http::{request::RequestContext, tower::ServiceExt};
+
+///Set by the Lambda runtime in every function environment.
+pub const LAMBDA_RUNTIME_API_ENV: &str = "AWS_LAMBDA_RUNTIME_API";
+
+#[derive(Debug, thiserror::Error)]
+pub enum ServeError {
+    #[error("Could not serve on {address} : {source}")]
+    Io {
+        address: SocketAddr,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("Lambda runtime failed : {0}")]
+    Lambda(lambda_http::Error),
+}
+
+///How `serve` runs the app outside of Lambda, and how stage prefixes are handled in both modes.
+#[derive(Debug, Clone)]
+pub struct ServeOptions {
+    ///Defaults to `0.0.0.0:3000`.
+    pub address: SocketAddr,
+    ///How long in-flight requests may take to finish after SIGTERM or SIGINT. Defaults to 30 seconds.
+    pub drain_timeout: Duration,
+    ///Removes the api gateway stage, eg: `/prod/posts` becomes `/posts`. Defaults to true.
+    pub strip_stage: bool,
+    ///Stage stripped outside of Lambda, so that deployed urls also work locally.
+    pub stage: Option<String>,
+}
+
+impl Default for ServeOptions {
+    fn default() -> Self {
+        Self {
+            address: SocketAddr::from((Ipv4Addr::UNSPECIFIED, 3000)),
+            drain_timeout: Duration::from_secs(30),
+            strip_stage: true,
+            stage: None,
+        }
+    }
+}
+
+impl ServeOptions {
+    pub fn with_address(mut self, address: SocketAddr) -> Self {
+        self.address = address;
+        self
+    }
+
+    pub fn with_drain_timeout(mut self, drain_timeout: Duration) -> Self {
+        self.drain_timeout = drain_timeout;
+        self
+    }
+
+    pub fn with_stage(mut self, stage: impl Into<String>) -> Self {
+        self.stage = Some(stage.into());
+        self
+    }
+
+    pub fn without_stage_stripping(mut self) -> Self {
+        self.strip_stage = false;
+        self
+    }
+}
+
+pub fn is_lambda() -> bool {
+    std::env::var_os(LAMBDA_RUNTIME_API_ENV).is_some()
+}
+
+///Runs the app with the Lambda runtime when deployed to Lambda, or on a local TCP listener
+///otherwise.
+///
+///```ignore
+///let app = routes.with_web_core(web_core_options);
+///web_core::serve(app, ServeOptions::default()).await?;
+///```
+pub async fn serve(app: Router, options: ServeOptions) -> Result<(), ServeError> {
+    let ServeOptions {
+        address,
+        drain_timeout,
+        strip_stage,
+        stage,
+    } = options;
+
+    if is_lambda() {
+        let app = app.map_request(move |req: lambda_http::Request| {
+            strip_stage_prefix(req, stage.as_deref(), strip_stage)
+        });
+        return lambda_http::run(app).await.map_err(ServeError::Lambda);
+    }
+
+    let app =
+        app.map_request(move |req: Request| strip_stage_prefix(req, stage.as_deref(), strip_stage));
+    let listener = tokio::net::TcpListener::bind(address)
+        .await
+        .map_err(|source| ServeError::Io { address, source })?;
+    tracing::info!(%address, "listening");
+
+    let (signal_sender, signal_receiver) = tokio::sync::oneshot::channel();
+    let server = axum::serve(
+        listener,
+        axum::ServiceExt::<Request>::into_make_service_with_connect_info::<SocketAddr>(app),
+    )
+    .with_graceful_shutdown(async move {
+        shutdown_signal().await;
+        let _ = signal_sender.send(());
+    })
+    .into_future();
+
+    let result = match select(pin!(server), signal_receiver).await {
+        Either::Left((result, _)) => result,
+        Either::Right((_, server)) => {
+            tracing::info!(drain_timeout = ?drain_timeout, "shutting down");
+            match tokio::time::timeout(drain_timeout, server).await {
+                Ok(result) => result,
+                Err(_) => {
+                    tracing::warn!("drain timeout elapsed, dropping remaining connections");
+                    Ok(())
+                }
+            }
+        }
+    };
+    result.map_err(|source| ServeError::Io { address, source })
+}
+
+async fn shutdown_signal() {
+    let ctrl_c = pin!(async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            tracing::error!(error = ?e, "could not listen for SIGINT");
+            std::future::pending::<()>().await;
+        }
+    });
+
+    #[cfg(unix)]
+    let terminate = pin!(async {
+        use tokio::signal::unix::{SignalKind, signal};
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                terminate.recv().await;
+            }
+            Err(e) => {
+                tracing::error!(error = ?e, "could not listen for SIGTERM");
+                std::future::pending::<()>().await;
+            }
+        }
+    });
+    #[cfg(not(unix))]
+    let terminate = pin!(std::future::pending::<()>());
+
+    select(ctrl_c, terminate).await;
+}
+
+///Stage of the api gateway request, or the configured one outside of Lambda.
+///HTTP apis using the `$default` stage have no prefix.
+fn request_stage<'a, B>(req: &'a http::Request<B>, stage: Option<&'a str>) -> Option<&'a str> {
+    let context_stage = match req.extensions().get::<RequestContext>() {
+        Some(RequestContext::ApiGatewayV2(context)) => context.stage.as_deref(),
+        Some(RequestContext::ApiGatewayV1(context)) => context.stage.as_deref(),
+        Some(RequestContext::WebSocket(context)) => context.stage.as_deref(),
+        Some(RequestContext::Alb(_)) | None => stage,
+    };
+    context_stage.filter(|x| !x.is_empty() && *x != "$default")
+}
+
+fn strip_stage_prefix<B>(
+    mut req: http::Request<B>,
+    stage: Option<&str>,
+    strip_stage: bool,
+) -> http::Request<B> {
+    let Some(stage) = request_stage(&req, stage).filter(|_| strip_stage) else {
+        return req;
+    };
+    let path = req.uri().path();
+    let Some(rest) = path
+        .strip_prefix('/')
+        .and_then(|x| x.strip_prefix(stage))
+        .filter(|x| x.is_empty() || x.starts_with('/'))
+    else {
+        return req;
+    };
+    let path_and_query = match (rest, req.uri().query()) {
+        ("", Some(query)) => format!("/?{query}"),
+        ("", None) => String::from("/"),
+        (rest, Some(query)) => format!("{rest}?{query}"),
+        (rest, None) => rest.to_string(),
+    };
+    let mut parts = req.uri().clone().into_parts();
+    if let Ok(path_and_query) = PathAndQuery::try_from(path_and_query) {
+        parts.path_and_query = Some(path_and_query);
+    }
+    if let Ok(uri) = Uri::from_parts(parts) {
+        *req.uri_mut() = uri;
+    }
+    req
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stripped(uri: &str, stage: Option<&str>, strip_stage: bool) -> String {
+        let req = http::Request::get(uri).body(()).unwrap();
+        strip_stage_prefix(req, stage, strip_stage)
+            .uri()
+            .to_string()
+    }
+
+    #[test]
+    fn stage_prefix_is_stripped() {
+        assert_eq!(stripped("/prod/x", Some("prod"), true), "/x");
+        assert_eq!(stripped("/prod/x/y?q=1", Some("prod"), true), "/x/y?q=1");
+        assert_eq!(stripped("/prod", Some("prod"), true), "/");
+        assert_eq!(stripped("/prod/", Some("prod"), true), "/");
+        assert_eq!(stripped("/prod?q=1", Some("prod"), true), "/?q=1");
+    }
+
+    #[test]
+    fn only_whole_segments_are_stripped() {
+        assert_eq!(
+            stripped("/production/x", Some("prod"), true),
+            "/production/x"
+        );
+        assert_eq!(stripped("/x/prod", Some("prod"), true), "/x/prod");
+    }
+
+    #[test]
+    fn default_stage_has_no_prefix() {
+        assert_eq!(
+            stripped("/$default/x", Some("$default"), true),
+            "/$default/x"
+        );
+        assert_eq!(stripped("/x", Some(""), true), "/x");
+        assert_eq!(stripped("/prod/x", None, true), "/prod/x");
+    }
+
+    #[test]
+    fn stripping_can_be_turned_off() {
+        assert_eq!(stripped("/prod/x", Some("prod"), false), "/prod/x");
+    }
+
+    #[test]
+    fn stage_of_the_api_gateway_request_wins() {
+        let context = lambda_http::aws_lambda_events::apigw::ApiGatewayV2httpRequestContext {
+            stage: Some(String::from("dev")),
+            ..Default::default()
+        };
+        let mut req = http::Request::get("/dev/x").body(()).unwrap();
+        req.extensions_mut()
+            .insert(RequestContext::ApiGatewayV2(context));
+
+        assert_eq!(strip_stage_prefix(req, Some("prod"), true).uri(), "/x");
+    }
+}
@@ -1,7 +1,6 @@
 use std::{
     any::{Any, TypeId},
     fmt::{Debug, Display},
-    usize,
 };
 
 use async_trait::async_trait;
@@ -9,6 +8,8 @@ use axum::{Router, body::to_bytes, http::Request};
 use http::StatusCode;
 use lambda_http::{Body, tower::ServiceExt};
 
+pub use crate::error::problem_details::ProblemDetails;
+
 pub trait JsonType:
     serde::Serialize + serde::de::DeserializeOwned + Debug + Send + Sync + 'static
 {
@@ -41,6 +42,9 @@ impl<E: JsonType> ErrorResponse<E> {
     }
 }
 
+///Error response of apis rendered with `ErrorFormat::ProblemJson`.
+pub type ProblemDetailsResponse = ErrorResponse<ProblemDetails>;
+
 #[async_trait]
 pub trait RouterExtensions {
     async fn post_api<T, R, E>(self, path: &str, request: Option<T>) -> Result<R, ErrorResponse<E>>
@@ -0,0 +1,89 @@
+use std::{
+    fmt::Display,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+    str::FromStr,
+};
+
+#[derive(Debug, thiserror::Error)]
+#[error("Invalid CIDR `{0}`.")]
+pub struct InvalidCidr(pub String);
+
+///An ip network such as `10.0.0.0/8` or `2001:db8::/32`. A plain address is a network of one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IpCidr {
+    address: IpAddr,
+    prefix: u8,
+}
+
+impl IpCidr {
+    pub fn new(address: IpAddr, prefix: u8) -> Result<Self, InvalidCidr> {
+        let max_prefix = match address {
+            IpAddr::V4(_) => 32,
+            IpAddr::V6(_) => 128,
+        };
+        if prefix > max_prefix {
+            return Err(InvalidCidr(format!("{address}/{prefix}")));
+        }
+        Ok(Self { address, prefix })
+    }
+
+    ///IPv4 mapped IPv6 addresses (`::ffff:10.0.0.1`) are compared as IPv4.
+    pub fn contains(&self, ip: &IpAddr) -> bool {
+        let ip = match ip {
+            IpAddr::V6(v6) => v6.to_ipv4_mapped().map(IpAddr::V4).unwrap_or(*ip),
+            IpAddr::V4(_) => *ip,
+        };
+        match (self.address, ip) {
+            (IpAddr::V4(network), IpAddr::V4(ip)) => {
+                let mask = u32::MAX.checked_shl(32 - self.prefix as u32).unwrap_or(0);
+                u32::from(network) & mask == u32::from(ip) & mask
+            }
+            (IpAddr::V6(network), IpAddr::V6(ip)) => {
+                let mask = u128::MAX.checked_shl(128 - self.prefix as u32).unwrap_or(0);
+                u128::from(network) & mask == u128::from(ip) & mask
+            }
+            _ => false,
+        }
+    }
+
+    ///Loopback and private ranges, for apps running behind a proxy on the same network.
+    pub fn private_networks() -> Vec<IpCidr> {
+        vec![
+            IpCidr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 0)), 8),
+            IpCidr::new(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 0)), 8),
+            IpCidr::new(IpAddr::V4(Ipv4Addr::new(172, 16, 0, 0)), 12),
+            IpCidr::new(IpAddr::V4(Ipv4Addr::new(192, 168, 0, 0)), 16),
+            IpCidr::new(IpAddr::V6(Ipv6Addr::LOCALHOST), 128),
+            IpCidr::new(IpAddr::V6(Ipv6Addr::new(0xfc00, 0, 0, 0, 0, 0, 0, 0)), 7),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+}
+
+impl FromStr for IpCidr {
+    type Err = InvalidCidr;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || InvalidCidr(s.to_string());
+        match s.trim().split_once('/') {
+            Some((address, prefix)) => {
+                let address = address.parse::<IpAddr>().map_err(|_| invalid())?;
+                let prefix = prefix.parse::<u8>().map_err(|_| invalid())?;
+                IpCidr::new(address, prefix)
+            }
+            None => {
+                let address = s.trim().parse::<IpAddr>().map_err(|_| invalid())?;
+                let prefix = if address.is_ipv4() { 32 } else { 128 };
+                IpCidr::new(address, prefix)
+            }
+        }
+    }
+}
+
+impl Display for IpCidr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.address, self.prefix)
+    }
+}
@@ -1 +1,2 @@
+pub mod ip_cidr;
 pub mod signatory;
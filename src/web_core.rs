@@ -1,11 +1,23 @@
-use std::sync::Arc;
+use std::{collections::HashSet, sync::Arc};
 
 use crate::{
     auth::auth_service::AuthService,
-    cors::WithCorsLayer,
+    config::ConfigError,
+    cors::{CorsError, CorsOptions, WithCorsLayer},
+    error::{ErrorFormat, localization::MessageCatalog, reporting::ErrorReporter},
+    health::{HealthOptions, HealthRoutes},
+    metrics::{MetricsLayer, MetricsOptions},
     middleware::{
-        logging_middleware::LoggingMiddlewareLayer, middleware_handler::crate_middleware_handler,
+        catch_panic::CatchPanicLayer,
+        client_ip::{ClientIpLayer, ClientIpOptions},
+        error_rendering::{ErrorRenderingLayer, ErrorRenderingOptions},
+        logging_middleware::{LogFormat, LoggingMiddlewareLayer, LoggingOptions, init_logging},
+        middleware_handler::crate_middleware_handler,
+        request_id::RequestIdLayer,
+        request_limits::{RequestLimitsLayer, RequestLimitsOptions},
+        security_headers::{SecurityHeadersLayer, SecurityHeadersOptions},
     },
+    route_group::RouteGroups,
 };
 use axum::{Router, body::Body, extract::Request, middleware::Next, response::Response};
 
@@ -13,7 +25,9 @@ pub trait WebCore<T>
 where
     T: Clone + Send + Sync + 'static,
 {
+    ///Panics when the options are invalid, see `try_with_web_core`.
     fn with_web_core(self, options: WebCoreOptions<T>) -> Router;
+    fn try_with_web_core(self, options: WebCoreOptions<T>) -> Result<Router, WebCoreError>;
     fn with_middleware<F, Fut>(self, header_handler: F) -> Router<WebCoreState<T>>
     where
         F: Fn(Request<Body>, Next) -> Fut + Send + Sync + Clone + 'static,
@@ -25,13 +39,61 @@ where
     T: Clone + Send + Sync + 'static,
 {
     fn with_web_core(self, options: WebCoreOptions<T>) -> Router {
+        match self.try_with_web_core(options) {
+            Ok(router) => router,
+            Err(e) => panic!("{e}"),
+        }
+    }
+
+    fn try_with_web_core(self, options: WebCoreOptions<T>) -> Result<Router, WebCoreError> {
         let WebCoreOptions {
             web_core_state,
-            frontend_urls: frontend_url,
+            cors,
+            error_rendering,
+            log_format,
+            client_ip,
+            logging,
+            security_headers,
+            request_limits,
+            health,
+            metrics,
+            route_groups,
         } = options;
-        self.with_logging_layer()
-            .with_cors_layer(frontend_url)
-            .with_state(web_core_state)
+        let cors = cors.build()?;
+        if let Some(log_format) = log_format {
+            init_logging(log_format);
+        }
+        let (router, own_cors_routes) = match route_groups {
+            Some(route_groups) => {
+                tracing::info!("Routes :\n{}", route_groups.route_table());
+                let (groups, own_cors_routes) = route_groups.build(&web_core_state.auth_service)?;
+                (self.merge(groups), own_cors_routes)
+            }
+            None => (self, HashSet::new()),
+        };
+        let router = router
+            .with_catch_panic_layer()
+            .with_request_limits_layer(request_limits)
+            .with_error_rendering_layer(error_rendering)
+            .with_security_headers_layer(security_headers)
+            .with_logging_layer_options(logging)
+            .with_client_ip_layer(client_ip)
+            .with_request_id_layer();
+        let router = match metrics.is_some() {
+            true => router.with_metrics_layer(),
+            false => router,
+        };
+        let router = router.with_cors_layer_except(cors, own_cors_routes);
+        //Mounted after the layers, so that probes and scrapes are neither logged nor reported.
+        let router = match health {
+            Some(health) => router.with_health_routes(health),
+            None => router,
+        };
+        let router = match metrics {
+            Some(metrics) => router.with_metrics_route(metrics),
+            None => router,
+        };
+        Ok(router.with_state(web_core_state))
     }
 
     fn with_middleware<F, Fut>(self, handler: F) -> Router<WebCoreState<T>>
@@ -44,12 +106,31 @@ where
     }
 }
 
+#[derive(Debug, thiserror::Error)]
+pub enum WebCoreError {
+    #[error(transparent)]
+    Cors(#[from] CorsError),
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+    #[error("Could not set up the database : {0}")]
+    Database(#[source] crate::error::Error),
+}
+
 pub struct WebCoreOptions<T>
 where
     T: Clone + Send + Sync + 'static,
 {
     web_core_state: WebCoreState<T>,
-    frontend_urls: Vec<String>,
+    cors: CorsOptions,
+    error_rendering: ErrorRenderingOptions,
+    log_format: Option<LogFormat>,
+    client_ip: ClientIpOptions,
+    logging: LoggingOptions,
+    security_headers: SecurityHeadersOptions,
+    request_limits: RequestLimitsOptions,
+    health: Option<HealthOptions>,
+    metrics: Option<MetricsOptions>,
+    route_groups: Option<RouteGroups<T>>,
 }
 
 impl<T> WebCoreOptions<T>
@@ -59,14 +140,134 @@ where
     pub fn new(web_core_state: WebCoreState<T>) -> Self {
         Self {
             web_core_state,
-            frontend_urls: vec![],
+            cors: CorsOptions::default(),
+            error_rendering: ErrorRenderingOptions::default(),
+            log_format: None,
+            client_ip: ClientIpOptions::default(),
+            logging: LoggingOptions::default(),
+            security_headers: SecurityHeadersOptions::default(),
+            request_limits: RequestLimitsOptions::default(),
+            health: None,
+            metrics: None,
+            route_groups: None,
         }
     }
 
+    ///Allows `frontend_url` and its `www.` counterpart as CORS origins.
+    ///Wildcard origins and hosts without a domain, eg: `localhost`, have no counterpart.
     pub fn with_frontend_url(mut self, frontend_url: String) -> Self {
-        self.frontend_urls.push(frontend_url);
+        let frontend_url = frontend_url.trim_end_matches('/');
+        self.cors = self.cors.with_origin(frontend_url);
+        if let Some(counterpart) = www_counterpart(frontend_url) {
+            self.cors = self.cors.with_origin(counterpart);
+        }
+        self
+    }
+
+    ///Replaces the CORS options, including origins added with `with_frontend_url`.
+    pub fn with_cors(mut self, cors: CorsOptions) -> Self {
+        self.cors = cors;
+        self
+    }
+
+    ///Defaults to `ErrorFormat::Json`.
+    pub fn with_error_format(mut self, format: ErrorFormat) -> Self {
+        self.error_rendering = self.error_rendering.with_format(format);
+        self
+    }
+
+    ///Translates error and validation messages using the `Accept-Language` of the request.
+    pub fn with_message_catalog(mut self, catalog: MessageCatalog) -> Self {
+        self.error_rendering = self.error_rendering.with_catalog(catalog);
+        self
+    }
+
+    ///Every 5xx response is sent to the reporters. Can be called multiple times.
+    ///Errors are printed to stderr when no reporter is registered.
+    pub fn with_error_reporter(mut self, reporter: impl ErrorReporter + 'static) -> Self {
+        self.error_rendering = self.error_rendering.with_reporter(reporter);
+        self
+    }
+
+    ///Installs a `tracing` subscriber with the given format when the router is built.
+    ///Leave it unset to install your own subscriber.
+    pub fn with_log_format(mut self, log_format: LogFormat) -> Self {
+        self.log_format = Some(log_format);
+        self
+    }
+
+    ///Header and body logging, with redaction, size caps and sampling.
+    pub fn with_logging(mut self, logging: LoggingOptions) -> Self {
+        self.logging = logging;
         self
     }
+
+    ///Defaults to `SecurityHeadersOptions::default()`, use `SecurityHeadersOptions::none()` to
+    ///send none of them.
+    pub fn with_security_headers(mut self, security_headers: SecurityHeadersOptions) -> Self {
+        self.security_headers = security_headers;
+        self
+    }
+
+    ///Timeout, body size and in-flight limits of every request.
+    ///Route groups can replace them with `with_request_limits_layer`.
+    pub fn with_request_limits(mut self, request_limits: RequestLimitsOptions) -> Self {
+        self.request_limits = request_limits;
+        self
+    }
+
+    ///Mounts `/health/live` and `/health/ready`, outside of any auth layer.
+    ///Probes skip the web-core layers, so they are not logged and failures are not reported.
+    pub fn with_health(mut self, health: HealthOptions) -> Self {
+        self.health = Some(health);
+        self
+    }
+
+    ///Records request count, 5xx count and latency per route template, and mounts them in the
+    ///Prometheus text format on `MetricsOptions::path`, along with auth failures, pool usage
+    ///and `post_api` latency.
+    pub fn with_metrics(mut self, metrics: MetricsOptions) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    ///Mounts the routes of each group behind its own stack, and logs the route table.
+    ///Routes of groups with their own CORS options skip the ones of `with_cors`.
+    pub fn with_route_groups(mut self, route_groups: RouteGroups<T>) -> Self {
+        self.route_groups = Some(route_groups);
+        self
+    }
+
+    ///Trusted proxies and header sources used to resolve `ClientIp`.
+    ///By default only the immediate peer is used.
+    pub fn with_client_ip(mut self, client_ip: ClientIpOptions) -> Self {
+        self.client_ip = client_ip;
+        self
+    }
+
+    ///Message returned when a query violates `constraint`, instead of naming the constraint.
+    pub fn with_constraint_message(
+        mut self,
+        constraint: impl Into<String>,
+        message: impl Into<String>,
+    ) -> Self {
+        self.error_rendering = self
+            .error_rendering
+            .with_constraint_message(constraint, message);
+        self
+    }
+}
+
+///`https://www.example.com` for `https://example.com` and the other way around.
+fn www_counterpart(url: &str) -> Option<String> {
+    let (scheme, host) = url.split_once("://")?;
+    if !matches!(scheme, "http" | "https") || host.starts_with('*') || !host.contains('.') {
+        return None;
+    }
+    Some(match host.strip_prefix("www.") {
+        Some(host) => format!("{scheme}://{host}"),
+        None => format!("{scheme}://www.{host}"),
+    })
 }
 
 #[derive(Clone)]
@@ -82,7 +283,7 @@ where
     pub fn new(auth_service: AuthService, additional_state: T) -> Self {
         Self {
             auth_service: Arc::new(auth_service),
-            additional_state: additional_state,
+            additional_state,
         }
     }
 }
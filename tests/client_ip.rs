@@ -0,0 +1,150 @@
+use std::net::{IpAddr, SocketAddr};
+
+use axum::{
+    extract::ConnectInfo,
+    http::{Extensions, HeaderMap, HeaderValue},
+};
+use web_core::middleware::client_ip::{ClientIpOptions, ForwardedHeader};
+
+fn peer(address: &str) -> Extensions {
+    let mut extensions = Extensions::new();
+    extensions.insert(ConnectInfo(SocketAddr::new(address.parse().unwrap(), 443)));
+    extensions
+}
+
+fn headers(values: &[(&'static str, &str)]) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    for (name, value) in values {
+        headers.append(*name, HeaderValue::from_str(value).unwrap());
+    }
+    headers
+}
+
+fn ip(address: &str) -> Option<IpAddr> {
+    Some(address.parse().unwrap())
+}
+
+fn behind_proxies() -> ClientIpOptions {
+    ClientIpOptions::default()
+        .with_trusted_proxy("10.0.0.0/8".parse().unwrap())
+        .with_trusted_proxy("2001:db8:ffff::/48".parse().unwrap())
+}
+
+#[test]
+fn untrusted_peer_is_the_client() {
+    let headers = headers(&[("x-forwarded-for", "1.2.3.4")]);
+
+    let resolved = behind_proxies().resolve(&headers, &peer("203.0.113.9"));
+
+    assert_eq!(resolved, ip("203.0.113.9"));
+}
+
+#[test]
+fn x_forwarded_for_is_walked_right_to_left() {
+    let headers = headers(&[
+        ("x-forwarded-for", "6.6.6.6, 198.51.100.7"),
+        ("x-forwarded-for", "10.0.0.2"),
+    ]);
+
+    let resolved = behind_proxies().resolve(&headers, &peer("10.0.0.1"));
+
+    assert_eq!(resolved, ip("198.51.100.7"));
+}
+
+#[test]
+fn unreadable_hop_stops_the_walk() {
+    let headers = headers(&[("x-forwarded-for", "6.6.6.6, garbage, 10.0.0.2")]);
+
+    let resolved = behind_proxies().resolve(&headers, &peer("10.0.0.1"));
+
+    assert_eq!(resolved, ip("10.0.0.1"));
+}
+
+#[test]
+fn chain_of_trusted_proxies_gives_the_leftmost() {
+    let headers = headers(&[("x-forwarded-for", "10.0.0.3, 10.0.0.2")]);
+
+    let resolved = behind_proxies().resolve(&headers, &peer("10.0.0.1"));
+
+    assert_eq!(resolved, ip("10.0.0.3"));
+}
+
+#[test]
+fn spoofed_forwarded_is_ignored_when_reading_x_forwarded_for() {
+    let headers = headers(&[
+        ("forwarded", "for=1.2.3.4"),
+        ("x-forwarded-for", "198.51.100.7"),
+    ]);
+
+    let resolved = behind_proxies().resolve(&headers, &peer("10.0.0.1"));
+
+    assert_eq!(resolved, ip("198.51.100.7"));
+}
+
+#[test]
+fn spoofed_x_forwarded_for_is_ignored_when_reading_forwarded() {
+    let headers = headers(&[
+        ("x-forwarded-for", "1.2.3.4"),
+        ("forwarded", "for=198.51.100.7;proto=https"),
+    ]);
+
+    let resolved = behind_proxies()
+        .with_header(ForwardedHeader::Forwarded)
+        .resolve(&headers, &peer("10.0.0.1"));
+
+    assert_eq!(resolved, ip("198.51.100.7"));
+}
+
+#[test]
+fn forwarded_values_are_parsed() {
+    let options = behind_proxies().with_header(ForwardedHeader::Forwarded);
+    let cases = [
+        ("for=198.51.100.7", "198.51.100.7"),
+        ("for=\"198.51.100.7\"", "198.51.100.7"),
+        ("for=\"198.51.100.7:4711\"", "198.51.100.7"),
+        ("For=\"[2001:db8:cafe::17]\"", "2001:db8:cafe::17"),
+        (
+            "proto=https;for=\"[2001:db8:cafe::17]:4711\";by=10.0.0.1",
+            "2001:db8:cafe::17",
+        ),
+        (
+            "for=198.51.100.7, for=\"[2001:db8:ffff::1]:80\"",
+            "198.51.100.7",
+        ),
+    ];
+
+    for (forwarded, expected) in cases {
+        let headers = headers(&[("forwarded", forwarded)]);
+
+        let resolved = options.resolve(&headers, &peer("10.0.0.1"));
+
+        assert_eq!(resolved, ip(expected), "{forwarded}");
+    }
+}
+
+#[test]
+fn obfuscated_forwarded_node_stops_the_walk() {
+    let headers = headers(&[("forwarded", "for=1.2.3.4, for=_hidden")]);
+
+    let resolved = behind_proxies()
+        .with_header(ForwardedHeader::Forwarded)
+        .resolve(&headers, &peer("10.0.0.1"));
+
+    assert_eq!(resolved, ip("10.0.0.1"));
+}
+
+#[test]
+fn cloudfront_viewer_address_is_only_read_when_picked() {
+    let headers = headers(&[
+        ("cloudfront-viewer-address", "2001:db8::1:46532"),
+        ("x-forwarded-for", "198.51.100.7"),
+    ]);
+
+    let default = behind_proxies().resolve(&headers, &peer("10.0.0.1"));
+    let cloudfront = behind_proxies()
+        .with_header(ForwardedHeader::CloudFrontViewerAddress)
+        .resolve(&headers, &peer("10.0.0.1"));
+
+    assert_eq!(default, ip("198.51.100.7"));
+    assert_eq!(cloudfront, ip("2001:db8::1"));
+}
@@ -0,0 +1,54 @@
+//!Fixtures shared by the integration tests. Each test binary only uses some of them.
+#![allow(dead_code)]
+
+use std::time::Duration;
+
+use axum::{
+    Router,
+    body::{Body, to_bytes},
+    http::Request,
+    response::Response,
+};
+use lambda_http::tower::ServiceExt;
+use serde_json::Value;
+use web_core::{
+    auth::{auth_options::AuthOptions, auth_service::AuthService},
+    web_core::{WebCoreOptions, WebCoreState},
+};
+
+pub const SECRET: &str = "secret";
+
+///Signs tokens with `SECRET`, access and refresh tokens last a minute.
+pub fn auth_service() -> AuthService {
+    AuthService::new(AuthOptions::new(
+        String::from(SECRET),
+        Duration::from_secs(60),
+        Duration::from_secs(60),
+    ))
+}
+
+pub fn state() -> WebCoreState<()> {
+    WebCoreState::new(auth_service(), ())
+}
+
+pub fn web_core_options() -> WebCoreOptions<()> {
+    WebCoreOptions::new(state())
+}
+
+pub async fn send(app: &Router, request: Request<Body>) -> Response {
+    app.clone().oneshot(request).await.unwrap()
+}
+
+pub async fn get(app: &Router, path: &str) -> Response {
+    send(app, Request::get(path).body(Body::empty()).unwrap()).await
+}
+
+pub async fn text(response: Response) -> String {
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    String::from_utf8(body.to_vec()).unwrap()
+}
+
+pub async fn json(response: Response) -> Value {
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    serde_json::from_slice(&body).unwrap()
+}
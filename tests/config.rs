@@ -0,0 +1,24 @@
+#![cfg(unix)]
+
+use std::{ffi::OsStr, os::unix::ffi::OsStrExt};
+
+use web_core::config::{ConfigError, WebCoreConfig};
+
+#[test]
+fn non_unicode_variables_do_not_panic() {
+    //Only this test sets variables, with a prefix no other test reads.
+    unsafe {
+        std::env::set_var(OsStr::from_bytes(b"WCTEST_UNRELATED_\xff"), "value");
+        std::env::set_var("WCTEST_JWT_SECRET", OsStr::from_bytes(b"secret\xff"));
+    }
+
+    let error = WebCoreConfig::loader()
+        .with_env_prefix("WCTEST")
+        .load()
+        .unwrap_err();
+    assert!(
+        matches!(error, ConfigError::Malformed { .. }),
+        "unexpected error {error}"
+    );
+    assert!(error.to_string().contains("WCTEST_JWT_SECRET"));
+}
@@ -0,0 +1,71 @@
+mod common;
+
+use axum::{
+    Router,
+    body::Body,
+    http::{Request, header::ACCESS_CONTROL_ALLOW_ORIGIN},
+    routing::get,
+};
+use lambda_http::tower::ServiceExt;
+use web_core::{cors::CorsOptions, web_core::WebCore};
+
+async fn is_allowed(cors: &CorsOptions, origin: &str) -> bool {
+    let app: Router = Router::new()
+        .route("/", get(|| async { "ok" }))
+        .layer(cors.build().unwrap());
+    let request = Request::get("/")
+        .header("origin", origin)
+        .body(Body::empty())
+        .unwrap();
+    let response = app.oneshot(request).await.unwrap();
+    response.headers().contains_key(ACCESS_CONTROL_ALLOW_ORIGIN)
+}
+
+#[tokio::test]
+async fn origin_patterns_match_the_whole_origin() {
+    let cors = CorsOptions::default().with_origin_regex(r"https://pr-\d+\.example\.com");
+
+    assert!(is_allowed(&cors, "https://pr-12.example.com").await);
+    assert!(!is_allowed(&cors, "https://pr-12.example.com.evil.io").await);
+    assert!(!is_allowed(&cors, "http://evil.io?https://pr-12.example.com").await);
+}
+
+#[tokio::test]
+async fn wildcard_origins_compare_host_and_port() {
+    let cors = CorsOptions::default()
+        .with_origin("https://*.example.com")
+        .with_origin("http://*.local.test:3000");
+
+    assert!(is_allowed(&cors, "https://app.example.com").await);
+    assert!(is_allowed(&cors, "https://app.example.com:443").await);
+    assert!(!is_allowed(&cors, "https://app.example.com:8443").await);
+    assert!(!is_allowed(&cors, "https://evilexample.com").await);
+    assert!(is_allowed(&cors, "http://app.local.test:3000").await);
+    assert!(!is_allowed(&cors, "http://app.local.test").await);
+}
+
+#[tokio::test]
+async fn frontend_url_allows_its_www_counterpart() {
+    let allows = |frontend_url: &str, origin: &str| {
+        let app = Router::new()
+            .route("/", get(|| async { "ok" }))
+            .try_with_web_core(common::web_core_options().with_frontend_url(frontend_url.into()))
+            .unwrap();
+        let request = Request::get("/")
+            .header("origin", origin)
+            .body(Body::empty())
+            .unwrap();
+        async move {
+            let response = common::send(&app, request).await;
+            response.headers().contains_key(ACCESS_CONTROL_ALLOW_ORIGIN)
+        }
+    };
+
+    assert!(allows("https://example.com/", "https://www.example.com").await);
+    assert!(allows("https://www.example.com", "https://example.com").await);
+    assert!(allows("http://example.com", "http://www.example.com").await);
+    assert!(allows("http://www.example.com", "http://example.com").await);
+    assert!(!allows("http://example.com", "https://www.example.com").await);
+    assert!(allows("https://*.example.com", "https://app.example.com").await);
+    assert!(allows("http://localhost:3000", "http://localhost:3000").await);
+}
@@ -0,0 +1,220 @@
+use std::{
+    borrow::Cow,
+    error::Error as StdError,
+    fmt,
+    io::{self, Read},
+    net::TcpListener,
+    time::Duration,
+};
+
+use axum::http::StatusCode;
+use jsonwebtoken::errors::ErrorKind;
+use sqlx::error::DatabaseError;
+use web_core::{error::Error, something_went_wrong, web_core::WebCoreError};
+
+fn mapped(error: impl Into<Error>) -> (StatusCode, String) {
+    let error = error.into();
+    (error.status_code(), error.code().to_string())
+}
+
+fn expected(status: StatusCode, code: &str) -> (StatusCode, String) {
+    (status, code.to_string())
+}
+
+struct FailingReader;
+
+impl Read for FailingReader {
+    fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+        Err(io::Error::other("connection reset"))
+    }
+}
+
+#[test]
+fn serde_json_errors() {
+    let invalid_json = expected(StatusCode::BAD_REQUEST, "request.invalid_json");
+    assert_eq!(
+        mapped(serde_json::from_str::<u32>("{").unwrap_err()),
+        invalid_json
+    );
+    assert_eq!(
+        mapped(serde_json::from_str::<u32>(r#""a""#).unwrap_err()),
+        invalid_json
+    );
+    assert_eq!(
+        mapped(serde_json::from_str::<u32>("").unwrap_err()),
+        invalid_json
+    );
+    assert_eq!(
+        mapped(serde_json::from_reader::<_, u32>(FailingReader).unwrap_err()),
+        expected(StatusCode::INTERNAL_SERVER_ERROR, "internal_error")
+    );
+}
+
+///A postgres error as returned by the driver.
+#[derive(Debug)]
+struct PgError {
+    code: &'static str,
+    constraint: Option<&'static str>,
+}
+
+impl fmt::Display for PgError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "error {}", self.code)
+    }
+}
+
+impl StdError for PgError {}
+
+impl DatabaseError for PgError {
+    fn message(&self) -> &str {
+        "database error"
+    }
+
+    fn code(&self) -> Option<Cow<'_, str>> {
+        Some(Cow::Borrowed(self.code))
+    }
+
+    fn constraint(&self) -> Option<&str> {
+        self.constraint
+    }
+
+    fn as_error(&self) -> &(dyn StdError + Send + Sync + 'static) {
+        self
+    }
+
+    fn as_error_mut(&mut self) -> &mut (dyn StdError + Send + Sync + 'static) {
+        self
+    }
+
+    fn into_error(self: Box<Self>) -> Box<dyn StdError + Send + Sync + 'static> {
+        self
+    }
+
+    fn kind(&self) -> sqlx::error::ErrorKind {
+        use sqlx::error::ErrorKind;
+        match self.code {
+            "23505" => ErrorKind::UniqueViolation,
+            "23503" => ErrorKind::ForeignKeyViolation,
+            "23514" => ErrorKind::CheckViolation,
+            _ => ErrorKind::Other,
+        }
+    }
+}
+
+fn database_error(code: &'static str, constraint: Option<&'static str>) -> sqlx::Error {
+    sqlx::Error::Database(Box::new(PgError { code, constraint }))
+}
+
+#[test]
+fn sqlx_errors() {
+    assert_eq!(mapped(sqlx::Error::RowNotFound).0, StatusCode::NOT_FOUND);
+    assert_eq!(
+        mapped(database_error("23505", Some("users_email_key"))).0,
+        StatusCode::CONFLICT
+    );
+    assert_eq!(
+        mapped(database_error("23503", Some("posts_user_id_fkey"))).0,
+        StatusCode::UNPROCESSABLE_ENTITY
+    );
+    assert_eq!(
+        mapped(database_error("23514", Some("users_age_check"))).0,
+        StatusCode::UNPROCESSABLE_ENTITY
+    );
+    assert_eq!(
+        mapped(database_error("40001", None)).0,
+        StatusCode::SERVICE_UNAVAILABLE
+    );
+    assert_eq!(
+        mapped(sqlx::Error::PoolTimedOut).0,
+        StatusCode::SERVICE_UNAVAILABLE
+    );
+    assert_eq!(
+        mapped(database_error("42P01", None)),
+        expected(StatusCode::INTERNAL_SERVER_ERROR, "internal_error")
+    );
+    assert_eq!(
+        mapped(sqlx::Error::Protocol(String::from("unexpected message"))),
+        expected(StatusCode::INTERNAL_SERVER_ERROR, "internal_error")
+    );
+}
+
+#[tokio::test]
+async fn reqwest_errors() {
+    //Connections are accepted by the kernel but nothing ever answers.
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let error = reqwest::Client::new()
+        .get(format!("http://{}", listener.local_addr().unwrap()))
+        .timeout(Duration::from_millis(100))
+        .send()
+        .await
+        .unwrap_err();
+    assert_eq!(
+        mapped(error),
+        expected(StatusCode::SERVICE_UNAVAILABLE, "upstream.timeout")
+    );
+
+    let error = reqwest::Client::new()
+        .get("not a url")
+        .send()
+        .await
+        .unwrap_err();
+    assert_eq!(
+        mapped(error),
+        expected(StatusCode::INTERNAL_SERVER_ERROR, "internal_error")
+    );
+}
+
+#[test]
+fn jsonwebtoken_errors() {
+    let error = |kind: ErrorKind| jsonwebtoken::errors::Error::from(kind);
+
+    assert_eq!(
+        mapped(error(ErrorKind::ExpiredSignature)),
+        expected(StatusCode::UNAUTHORIZED, "auth.token_expired")
+    );
+    assert_eq!(
+        mapped(error(ErrorKind::InvalidSignature)),
+        expected(StatusCode::UNAUTHORIZED, "auth.token_invalid")
+    );
+    assert_eq!(
+        mapped(error(ErrorKind::InvalidToken)),
+        expected(StatusCode::UNAUTHORIZED, "auth.token_invalid")
+    );
+    assert_eq!(
+        mapped(error(ErrorKind::InvalidKeyFormat)),
+        expected(StatusCode::INTERNAL_SERVER_ERROR, "internal_error")
+    );
+    assert_eq!(
+        mapped(error(ErrorKind::InvalidAlgorithmName)),
+        expected(StatusCode::INTERNAL_SERVER_ERROR, "internal_error")
+    );
+}
+
+#[test]
+fn errors_are_displayed_without_debug_formatting() {
+    let error = WebCoreError::Database(Error::new_something_went_wrong_with_source(
+        "Error while connecting to the database",
+        io::Error::other("connection refused"),
+    ));
+    assert_eq!(
+        error.to_string(),
+        "Could not set up the database : Error while connecting to the database : connection refused"
+    );
+    assert_eq!(
+        error.source().and_then(|x| x.source()).unwrap().to_string(),
+        "connection refused"
+    );
+
+    assert_eq!(
+        something_went_wrong!("pool is closed").to_string(),
+        "pool is closed"
+    );
+    assert_eq!(
+        Error::new_not_found("User not found.").to_string(),
+        "User not found."
+    );
+    assert_eq!(
+        Error::new_not_found("").to_string(),
+        Error::new_not_found("").code()
+    );
+}
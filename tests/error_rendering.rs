@@ -0,0 +1,106 @@
+mod common;
+
+use axum::{
+    Router,
+    http::{StatusCode, header::CONTENT_TYPE},
+    routing::get,
+};
+use serde_json::json;
+use web_core::{
+    error::{
+        Error, ErrorFormat, conflict::ConflictError, problem_details::PROBLEM_JSON_CONTENT_TYPE,
+    },
+    middleware::error_rendering::is_reporting,
+    something_went_wrong,
+    web_core::{WebCore, WebCoreOptions},
+};
+
+#[tokio::test]
+async fn handlers_know_whether_errors_are_reported() {
+    let reporting = || async { is_reporting().to_string() };
+    let with_web_core: Router = Router::new()
+        .route("/", get(reporting))
+        .with_web_core(common::web_core_options());
+    let without_web_core: Router = Router::new().route("/", get(reporting));
+
+    assert_eq!(
+        common::text(common::get(&with_web_core, "/").await).await,
+        "true"
+    );
+    assert_eq!(
+        common::text(common::get(&without_web_core, "/").await).await,
+        "false"
+    );
+}
+
+#[tokio::test]
+async fn constraint_messages_are_per_app() {
+    let app = |options: WebCoreOptions<()>| -> Router {
+        let handler = || async {
+            let mut error = ConflictError::new(String::from("Violates unique constraint."));
+            error.constraint = Some(String::from("users_email_key"));
+            Err::<(), Error>(Error::Conflict(error))
+        };
+        Router::new()
+            .route("/", get(handler))
+            .with_web_core(options)
+    };
+    let with_message = app(common::web_core_options()
+        .with_constraint_message("users_email_key", "Email is already registered."));
+    let without_message = app(common::web_core_options());
+
+    let body = common::text(common::get(&with_message, "/").await).await;
+    assert!(body.contains("Email is already registered."));
+    let body = common::text(common::get(&without_message, "/").await).await;
+    assert!(body.contains("Violates unique constraint."));
+}
+
+fn problem_json_app() -> Router {
+    Router::new()
+        .route(
+            "/users/{id}",
+            get(|| async {
+                Err::<(), Error>(Error::new_field_validation_error("email", "Invalid email."))
+            }),
+        )
+        .route(
+            "/fail",
+            get(|| async { Err::<(), Error>(something_went_wrong!("database is down")) }),
+        )
+        .with_web_core(common::web_core_options().with_error_format(ErrorFormat::ProblemJson))
+}
+
+#[tokio::test]
+async fn field_errors_are_rendered_as_problem_json() {
+    let response = common::get(&problem_json_app(), "/users/1").await;
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(response.headers()[CONTENT_TYPE], PROBLEM_JSON_CONTENT_TYPE);
+    let body = common::json(response).await;
+    assert_eq!(body["type"], "about:blank");
+    assert_eq!(body["title"], "Bad Request");
+    assert_eq!(body["status"], 400);
+    assert_eq!(body["detail"], "Validation failed");
+    assert_eq!(body["instance"], "/users/1");
+    assert_eq!(body["code"], "validation.failed");
+    assert_eq!(
+        body["errors"],
+        json!({"email": [{"code": "validation.invalid", "message": "Invalid email."}]})
+    );
+}
+
+#[tokio::test]
+async fn server_errors_are_rendered_as_problem_json() {
+    let response = common::get(&problem_json_app(), "/fail").await;
+
+    assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(response.headers()[CONTENT_TYPE], PROBLEM_JSON_CONTENT_TYPE);
+    let body = common::json(response).await;
+    assert_eq!(body["type"], "about:blank");
+    assert_eq!(body["title"], "Internal Server Error");
+    assert_eq!(body["status"], 500);
+    assert_eq!(body["detail"], "Something went wrong");
+    assert_eq!(body["instance"], "/fail");
+    assert_eq!(body["code"], "internal_error");
+    assert!(body["error_id"].as_str().unwrap().starts_with("Error-"));
+}
@@ -0,0 +1,166 @@
+mod common;
+
+use axum::{
+    Router,
+    body::Body,
+    extract::DefaultBodyLimit,
+    http::{
+        Request, StatusCode,
+        header::{AUTHORIZATION, CONTENT_TYPE, IF_MODIFIED_SINCE},
+    },
+    routing::{get, post},
+};
+use axum_extra::{
+    TypedHeader,
+    headers::{Authorization, IfModifiedSince, authorization::Bearer},
+    typed_header::TypedHeaderRejection,
+};
+use serde::Deserialize;
+use web_core::{
+    error::{Error, ErrorFormat},
+    extract::{Json, Path, Query},
+    web_core::WebCore,
+};
+
+#[derive(Deserialize)]
+#[allow(dead_code)]
+struct NewUser {
+    name: String,
+}
+
+#[derive(Deserialize)]
+#[allow(dead_code)]
+struct Page {
+    page: u32,
+}
+
+fn app() -> Router {
+    Router::new()
+        .route(
+            "/json",
+            post(|_: Json<NewUser>| async {}).layer(DefaultBodyLimit::max(32)),
+        )
+        .route("/users/{id}", get(|_: Path<u32>| async {}))
+        .route("/no-params", get(|_: Path<u32>| async {}))
+        .route("/query", get(|_: Query<Page>| async {}))
+        .route(
+            "/token",
+            get(
+                |header: Result<TypedHeader<Authorization<Bearer>>, TypedHeaderRejection>| async {
+                    header.map(|_| ()).map_err(Error::from)
+                },
+            ),
+        )
+        .route(
+            "/header",
+            get(
+                |header: Result<TypedHeader<IfModifiedSince>, TypedHeaderRejection>| async {
+                    header.map(|_| ()).map_err(Error::from)
+                },
+            ),
+        )
+        .with_web_core(common::web_core_options().with_error_format(ErrorFormat::ProblemJson))
+}
+
+async fn rejection(request: Request<Body>) -> (StatusCode, String) {
+    let response = common::send(&app(), request).await;
+    let status = response.status();
+    let body = common::json(response).await;
+    (status, body["code"].as_str().unwrap().to_string())
+}
+
+fn post_json(body: &'static str) -> Request<Body> {
+    Request::post("/json")
+        .header(CONTENT_TYPE, "application/json")
+        .body(Body::from(body))
+        .unwrap()
+}
+
+fn get_with(path: &str, header: Option<(&str, &str)>) -> Request<Body> {
+    let builder = Request::get(path);
+    let builder = match header {
+        Some((name, value)) => builder.header(name, value),
+        None => builder,
+    };
+    builder.body(Body::empty()).unwrap()
+}
+
+fn expected(status: StatusCode, code: &str) -> (StatusCode, String) {
+    (status, code.to_string())
+}
+
+#[tokio::test]
+async fn json_rejections() {
+    assert_eq!(
+        rejection(post_json("{")).await,
+        expected(StatusCode::BAD_REQUEST, "request.invalid_json")
+    );
+    assert_eq!(
+        rejection(post_json(r#"{"name": 1}"#)).await,
+        expected(StatusCode::UNPROCESSABLE_ENTITY, "request.invalid_json")
+    );
+    assert_eq!(
+        rejection(Request::post("/json").body(Body::from("{}")).unwrap()).await,
+        expected(StatusCode::BAD_REQUEST, "request.invalid_json")
+    );
+    assert_eq!(
+        rejection(post_json(
+            r#"{"name": "a name longer than the body limit"}"#
+        ))
+        .await,
+        expected(StatusCode::PAYLOAD_TOO_LARGE, "payload_too_large")
+    );
+}
+
+#[tokio::test]
+async fn path_rejections() {
+    assert_eq!(
+        rejection(get_with("/users/abc", None)).await,
+        expected(StatusCode::BAD_REQUEST, "request.invalid_path")
+    );
+    //A route without the params of its handler is a bug, not a bad request.
+    assert_eq!(
+        rejection(get_with("/no-params", None)).await,
+        expected(StatusCode::INTERNAL_SERVER_ERROR, "internal_error")
+    );
+}
+
+#[tokio::test]
+async fn query_rejections() {
+    assert_eq!(
+        rejection(get_with("/query?page=abc", None)).await,
+        expected(StatusCode::BAD_REQUEST, "request.invalid_query")
+    );
+    assert_eq!(
+        rejection(get_with("/query", None)).await,
+        expected(StatusCode::BAD_REQUEST, "request.invalid_query")
+    );
+}
+
+#[tokio::test]
+async fn typed_header_rejections() {
+    assert_eq!(
+        rejection(get_with("/token", None)).await,
+        expected(StatusCode::UNAUTHORIZED, "auth.missing_token")
+    );
+    assert_eq!(
+        rejection(get_with(
+            "/token",
+            Some((AUTHORIZATION.as_str(), "Basic abc"))
+        ))
+        .await,
+        expected(StatusCode::UNAUTHORIZED, "auth.token_invalid")
+    );
+    assert_eq!(
+        rejection(get_with("/header", None)).await,
+        expected(StatusCode::BAD_REQUEST, "request.missing_header")
+    );
+    assert_eq!(
+        rejection(get_with(
+            "/header",
+            Some((IF_MODIFIED_SINCE.as_str(), "yesterday"))
+        ))
+        .await,
+        expected(StatusCode::BAD_REQUEST, "request.invalid_header")
+    );
+}
@@ -0,0 +1,29 @@
+use std::borrow::Cow;
+
+use validator_async::ValidationError;
+use web_core::error::field_validation::{FieldError, INVALID_FIELD_MESSAGE};
+
+#[test]
+fn rejected_value_is_not_echoed_without_message() {
+    let mut error = ValidationError::new("length");
+    error.add_param(Cow::from("value"), &"hunter2");
+    error.add_param(Cow::from("min"), &8);
+
+    let field_error = FieldError::from(&error);
+
+    assert_eq!(field_error.code, "length");
+    assert_eq!(field_error.message, INVALID_FIELD_MESSAGE);
+    assert!(!field_error.params.contains_key("value"));
+    assert!(
+        !serde_json::to_string(&field_error)
+            .unwrap()
+            .contains("hunter2")
+    );
+}
+
+#[test]
+fn message_is_kept() {
+    let error = ValidationError::new("length").with_message(Cow::from("Too short"));
+
+    assert_eq!(FieldError::from(&error).message, "Too short");
+}
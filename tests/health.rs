@@ -0,0 +1,80 @@
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use diesel::{
+    PgConnection,
+    r2d2::{ConnectionManager, Pool},
+};
+use web_core::health::{
+    DieselHealthCheck, HealthCheck, HealthCheckError, HealthOptions, HealthStatus,
+};
+
+#[derive(Clone, Default)]
+struct FailingCheck {
+    calls: Arc<AtomicUsize>,
+}
+
+#[async_trait]
+impl HealthCheck for FailingCheck {
+    fn name(&self) -> &str {
+        "database"
+    }
+
+    async fn check(&self, _timeout: Duration) -> Result<(), HealthCheckError> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        Err("password authentication failed for user admin".into())
+    }
+}
+
+#[tokio::test]
+async fn errors_are_hidden_by_default() {
+    let report = HealthOptions::default()
+        .with_check(FailingCheck::default())
+        .report()
+        .await;
+    assert_eq!(report.status, HealthStatus::Unavailable);
+    assert_eq!(report.checks[0].error, None);
+
+    let report = HealthOptions::default()
+        .with_check(FailingCheck::default())
+        .with_error_details()
+        .report()
+        .await;
+    assert!(report.checks[0].error.is_some());
+}
+
+#[tokio::test]
+async fn report_is_cached() {
+    let check = FailingCheck::default();
+    let options = HealthOptions::default()
+        .with_check(check.clone())
+        .with_cache_ttl(Duration::from_secs(60));
+    options.cached_report().await;
+    options.clone().cached_report().await;
+    assert_eq!(check.calls.load(Ordering::SeqCst), 1);
+
+    let options = options.with_cache_ttl(Duration::ZERO);
+    options.cached_report().await;
+    assert_eq!(check.calls.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn database_check_gives_up_with_the_probe() {
+    //Nothing listens on port 1, so the pool keeps retrying until its timeout, 30s by default.
+    let pool = Pool::builder().build_unchecked(ConnectionManager::<PgConnection>::new(
+        "postgres://user@127.0.0.1:1/db",
+    ));
+    let check = DieselHealthCheck::new(pool);
+
+    let started_at = Instant::now();
+    let result = check.check(Duration::from_millis(200)).await;
+
+    assert!(result.is_err());
+    assert!(started_at.elapsed() < Duration::from_secs(5));
+}
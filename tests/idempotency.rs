@@ -0,0 +1,128 @@
+mod common;
+
+use std::{
+    net::SocketAddr,
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+
+use axum::{
+    Router,
+    body::Body,
+    extract::ConnectInfo,
+    http::{Request, StatusCode},
+    routing::post,
+};
+use web_core::{
+    middleware::idempotency::{
+        IdempotencyLayer, IdempotencyOptions, IdempotencyRecord, IdempotencyStore,
+        MemoryIdempotencyStore, StoredResponse,
+    },
+    web_core::WebCore,
+};
+
+#[tokio::test]
+async fn key_is_released_when_the_handler_panics() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let handler_calls = calls.clone();
+    let app: Router = Router::new()
+        .route(
+            "/payments",
+            post(move || {
+                let calls = handler_calls.clone();
+                async move {
+                    if calls.fetch_add(1, Ordering::SeqCst) == 0 {
+                        panic!("first attempt fails");
+                    }
+                    "paid"
+                }
+            }),
+        )
+        .with_idempotency_layer(IdempotencyOptions::new(MemoryIdempotencyStore::new()))
+        .with_web_core(common::web_core_options());
+
+    let request = || {
+        Request::post("/payments")
+            .header("idempotency-key", "payment-1")
+            .body(Body::empty())
+            .unwrap()
+    };
+    let response = common::send(&app, request()).await;
+    assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+
+    //The key is released by a spawned task.
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    let response = common::send(&app, request()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn stale_claim_does_not_touch_a_later_claim() {
+    let store = MemoryIdempotencyStore::new();
+    let ttl = Duration::from_millis(20);
+    assert_eq!(
+        store
+            .begin("key", "fingerprint", "first", ttl)
+            .await
+            .unwrap(),
+        None
+    );
+    tokio::time::sleep(Duration::from_millis(30)).await;
+    assert_eq!(
+        store
+            .begin("key", "fingerprint", "second", ttl)
+            .await
+            .unwrap(),
+        None
+    );
+
+    //The first request outlived its ttl and finishes now.
+    store.release("key", "first").await.unwrap();
+    let response = StoredResponse {
+        status: 201,
+        headers: Vec::new(),
+        body: b"first".to_vec(),
+    };
+    store.complete("key", "first", response, ttl).await.unwrap();
+
+    let record = store
+        .begin("key", "fingerprint", "third", ttl)
+        .await
+        .unwrap();
+    assert_eq!(
+        record,
+        Some(IdempotencyRecord::InFlight {
+            fingerprint: String::from("fingerprint")
+        })
+    );
+}
+
+#[tokio::test]
+async fn anonymous_callers_do_not_share_keys() {
+    let app: Router = Router::new()
+        .route("/signup", post(|| async { "created" }))
+        .with_idempotency_layer(IdempotencyOptions::new(MemoryIdempotencyStore::new()))
+        .with_web_core(common::web_core_options());
+
+    let request = |peer: &str| {
+        let mut request = Request::post("/signup")
+            .header("idempotency-key", "signup-1")
+            .body(Body::empty())
+            .unwrap();
+        request
+            .extensions_mut()
+            .insert(ConnectInfo(SocketAddr::new(peer.parse().unwrap(), 443)));
+        request
+    };
+    let first = common::send(&app, request("203.0.113.1")).await;
+    let other_caller = common::send(&app, request("203.0.113.2")).await;
+    let retry = common::send(&app, request("203.0.113.1")).await;
+
+    assert!(first.headers().get("idempotent-replayed").is_none());
+    assert!(other_caller.headers().get("idempotent-replayed").is_none());
+    assert_eq!(retry.headers()["idempotent-replayed"], "true");
+}
@@ -0,0 +1,132 @@
+mod common;
+
+use std::collections::{BTreeMap, HashMap};
+
+use axum::{
+    Router,
+    body::Body,
+    http::{
+        Request,
+        header::{ACCEPT_LANGUAGE, CONTENT_LANGUAGE},
+    },
+    routing::post,
+};
+use serde_json::json;
+use web_core::{
+    error::{
+        Error,
+        code::CoreErrorCode,
+        field_validation::{FieldError, FieldValidationErrors},
+        localization::MessageCatalog,
+    },
+    web_core::WebCore,
+};
+
+fn catalog() -> MessageCatalog {
+    MessageCatalog::new()
+        .with_message(
+            "de",
+            CoreErrorCode::ValidationFailed,
+            "Validierung fehlgeschlagen",
+        )
+        .with_message("de", "length", "Mindestens {min} Zeichen")
+        .with_message(
+            "pt-BR",
+            CoreErrorCode::ValidationFailed,
+            "Falha na validação",
+        )
+        .with_message(
+            "fr",
+            CoreErrorCode::ValidationFailed,
+            "Échec de la validation",
+        )
+}
+
+#[test]
+fn highest_quality_locale_of_the_catalog_wins() {
+    let catalog = catalog();
+
+    assert_eq!(
+        catalog.negotiate(Some("fr;q=0.5, de;q=0.9, en;q=0.1")),
+        "de"
+    );
+    assert_eq!(catalog.negotiate(Some("es, fr;q=0.8")), "fr");
+    assert_eq!(catalog.negotiate(Some("de;q=0, fr;q=0.2")), "fr");
+}
+
+#[test]
+fn regions_fall_back_to_their_language() {
+    let catalog = catalog();
+
+    assert_eq!(catalog.negotiate(Some("de-AT")), "de");
+    assert_eq!(catalog.negotiate(Some("pt_BR")), "pt-br");
+    assert_eq!(
+        catalog.message("de-CH", "length"),
+        Some("Mindestens {min} Zeichen")
+    );
+}
+
+#[test]
+fn unknown_locales_use_the_default_locale() {
+    let catalog = catalog();
+
+    assert_eq!(catalog.negotiate(None), "en");
+    assert_eq!(catalog.negotiate(Some("ja, ko;q=0.5")), "en");
+    assert_eq!(
+        catalog.message("ja", CoreErrorCode::ValidationFailed.to_string().as_str()),
+        Some("Validation failed")
+    );
+    assert_eq!(
+        catalog.with_fallback_locale("de").negotiate(Some("ja")),
+        "de"
+    );
+}
+
+fn validation_app() -> Router {
+    let handler = || async {
+        let mut too_short = FieldError::new("length", "Too short");
+        too_short.params = BTreeMap::from([(String::from("min"), json!(8))]);
+        let fields = HashMap::from([(String::from("password"), vec![too_short])]);
+        Err::<(), Error>(Error::FieldValidationError(FieldValidationErrors::new(
+            fields,
+            vec![],
+        )))
+    };
+    Router::new()
+        .route("/signup", post(handler))
+        .with_web_core(common::web_core_options().with_message_catalog(catalog()))
+}
+
+async fn signup(accept_language: &str) -> (String, serde_json::Value) {
+    let request = Request::post("/signup")
+        .header(ACCEPT_LANGUAGE, accept_language)
+        .body(Body::empty())
+        .unwrap();
+    let response = common::send(&validation_app(), request).await;
+    let content_language = response.headers()[CONTENT_LANGUAGE]
+        .to_str()
+        .unwrap()
+        .to_string();
+    (content_language, common::json(response).await)
+}
+
+#[tokio::test]
+async fn field_messages_are_translated_with_their_params() {
+    let (content_language, body) = signup("de-AT, en;q=0.5").await;
+
+    assert_eq!(content_language, "de");
+    assert_eq!(body["error"], "Validierung fehlgeschlagen");
+    assert_eq!(
+        body["fields"]["password"][0]["message"],
+        "Mindestens 8 Zeichen"
+    );
+}
+
+#[tokio::test]
+async fn untranslated_field_messages_are_kept() {
+    let (content_language, body) = signup("fr").await;
+
+    assert_eq!(content_language, "fr");
+    assert_eq!(body["error"], "Échec de la validation");
+    assert_eq!(body["fields"]["password"][0]["message"], "Too short");
+}
@@ -0,0 +1,28 @@
+use web_core::{
+    bad_request,
+    error::{
+        Error,
+        code::{CoreErrorCode, ErrorCode},
+    },
+    validators::phone_number_validator::validate_phone,
+};
+
+#[test]
+fn bad_request_accepts_a_code_without_message() {
+    let error = bad_request!(code = CoreErrorCode::InvalidField);
+    assert_eq!(error.code(), CoreErrorCode::InvalidField.code());
+    assert_eq!(error.message(), "");
+
+    let error = bad_request!(code = CoreErrorCode::InvalidField, "Invalid name");
+    assert_eq!(error.code(), CoreErrorCode::InvalidField.code());
+    assert_eq!(error.message(), "Invalid name");
+}
+
+#[tokio::test]
+async fn phone_validation_keeps_its_field_key() {
+    let error = Error::from(validate_phone("not a phone").await.unwrap_err());
+    let Error::FieldValidationError(field_validation_errors) = error else {
+        panic!("expected a field validation error");
+    };
+    assert!(field_validation_errors.fields.contains_key("invalid_phone"));
+}
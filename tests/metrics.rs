@@ -0,0 +1,84 @@
+use std::time::Duration;
+
+use axum::{
+    Router,
+    body::Body,
+    http::{Method, Request, StatusCode},
+    middleware,
+    routing::any,
+};
+use lambda_http::tower::ServiceExt;
+use web_core::metrics::{MetricsRegistry, PoolStats, metrics, metrics_middleware};
+
+#[test]
+fn render_uses_prometheus_text_format() {
+    let registry = MetricsRegistry::default();
+    registry.record_request(
+        "GET",
+        "/users/{id}",
+        StatusCode::OK,
+        Duration::from_millis(20),
+    );
+    registry.record_request(
+        "GET",
+        "/users/{id}",
+        StatusCode::BAD_GATEWAY,
+        Duration::from_millis(300),
+    );
+    registry.record_auth_failure("auth.token_expired");
+    registry.record_outbound("api.example.com", false, Duration::from_secs(1));
+    registry.register_pool("main", || PoolStats {
+        connections: 5,
+        idle_connections: 2,
+        max_size: 10,
+    });
+
+    let rendered = registry.render();
+    let lines: Vec<&str> = rendered.lines().collect();
+    for expected in [
+        "# TYPE http_requests_total counter",
+        r#"http_requests_total{method="GET",route="/users/{id}",status_class="2xx"} 1"#,
+        r#"http_requests_total{method="GET",route="/users/{id}",status_class="5xx"} 1"#,
+        r#"http_request_errors_total{method="GET",route="/users/{id}",status_class="5xx"} 1"#,
+        "# TYPE http_request_duration_seconds histogram",
+        r#"http_request_duration_seconds_bucket{method="GET",route="/users/{id}",status_class="2xx",le="0.01"} 0"#,
+        r#"http_request_duration_seconds_bucket{method="GET",route="/users/{id}",status_class="2xx",le="0.025"} 1"#,
+        r#"http_request_duration_seconds_bucket{method="GET",route="/users/{id}",status_class="2xx",le="+Inf"} 1"#,
+        r#"http_request_duration_seconds_count{method="GET",route="/users/{id}",status_class="2xx"} 1"#,
+        r#"auth_failures_total{reason="auth.token_expired"} 1"#,
+        r#"outbound_request_duration_seconds_count{host="api.example.com",outcome="error"} 1"#,
+        r#"db_pool_connections{pool="main",state="in_use"} 3"#,
+        r#"db_pool_connections{pool="main",state="idle"} 2"#,
+        r#"db_pool_connections{pool="main",state="max"} 10"#,
+    ] {
+        assert!(
+            lines.contains(&expected),
+            "missing `{expected}` in:\n{rendered}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn middleware_records_route_template_and_bounded_method() {
+    let app: Router = Router::new()
+        .route("/metrics-test/{id}", any(|| async { "ok" }))
+        .layer(middleware::from_fn(metrics_middleware));
+    for method in [Method::GET, Method::from_bytes(b"PURGE").unwrap()] {
+        let request = Request::builder()
+            .method(method)
+            .uri("/metrics-test/42")
+            .body(Body::empty())
+            .unwrap();
+        app.clone().oneshot(request).await.unwrap();
+    }
+    let request = Request::get("/metrics-test-unknown")
+        .body(Body::empty())
+        .unwrap();
+    app.oneshot(request).await.unwrap();
+
+    let requests = &metrics().http_requests;
+    assert_eq!(requests.get(&["GET", "/metrics-test/{id}", "2xx"]), 1);
+    assert_eq!(requests.get(&["other", "/metrics-test/{id}", "2xx"]), 1);
+    assert_eq!(requests.get(&["PURGE", "/metrics-test/{id}", "2xx"]), 0);
+    assert!(requests.get(&["GET", "unmatched", "4xx"]) >= 1);
+}
@@ -0,0 +1,90 @@
+mod common;
+
+use axum::{
+    Router,
+    body::Body,
+    http::{Method, Request, StatusCode, header::CONTENT_SECURITY_POLICY},
+};
+use web_core::{
+    openapi::{
+        ApiRouter, OpenApi, OpenApiAsset, OpenApiAssets, OpenApiOptions, OpenApiRoutes, Operation,
+    },
+    web_core::WebCore,
+};
+
+fn docs_app(api_router: ApiRouter<()>, options: OpenApiOptions) -> Router {
+    let (router, operations) = api_router.into_parts();
+    router
+        .with_openapi_routes(
+            OpenApi::new("Blog", "1.0.0").with_operations(operations),
+            options,
+        )
+        .with_web_core(common::web_core_options())
+}
+
+async fn call(app: &Router, method: Method, path: &str) -> (StatusCode, Option<String>, String) {
+    let request = Request::builder()
+        .method(method)
+        .uri(path)
+        .body(Body::empty())
+        .unwrap();
+    let response = common::send(app, request).await;
+    let status = response.status();
+    let csp = response
+        .headers()
+        .get(CONTENT_SECURITY_POLICY)
+        .map(|x| x.to_str().unwrap().to_string());
+    (status, csp, common::text(response).await)
+}
+
+#[tokio::test]
+async fn route_uses_the_method_of_the_operation() {
+    let api_router = ApiRouter::new()
+        .route(Operation::post("/posts"), || async { "created" })
+        .route(Operation::get("/posts"), || async { "posts" });
+    let app = docs_app(api_router, OpenApiOptions::default());
+
+    assert_eq!(call(&app, Method::POST, "/posts").await.2, "created");
+    assert_eq!(call(&app, Method::GET, "/posts").await.2, "posts");
+    assert_eq!(
+        call(&app, Method::DELETE, "/posts").await.0,
+        StatusCode::METHOD_NOT_ALLOWED
+    );
+
+    let (_, _, document) = call(&app, Method::GET, "/openapi.json").await;
+    let document: serde_json::Value = serde_json::from_str(&document).unwrap();
+    assert!(document["paths"]["/posts"]["post"].is_object());
+    assert!(document["paths"]["/posts"]["get"].is_object());
+}
+
+#[tokio::test]
+async fn docs_page_pins_assets_and_allows_only_their_urls() {
+    let app = docs_app(
+        ApiRouter::new(),
+        OpenApiOptions::default().with_swagger_ui(),
+    );
+    let (status, csp, page) = call(&app, Method::GET, "/docs").await;
+    let csp = csp.unwrap();
+    assert_eq!(status, StatusCode::OK);
+    assert!(page.contains("https://unpkg.com/swagger-ui-dist@5.17.14/swagger-ui-bundle.js"));
+    assert!(csp.contains("https://unpkg.com/swagger-ui-dist@5.17.14/swagger-ui-bundle.js"));
+    assert!(!csp.contains("https://unpkg.com "));
+    assert!(!csp.contains("https://unpkg.com;"));
+
+    let assets = OpenApiAssets {
+        script: OpenApiAsset::new("https://cdn.example.com/redoc.js").with_integrity("sha384-abc"),
+        stylesheet: None,
+    };
+    let app = docs_app(
+        ApiRouter::new(),
+        OpenApiOptions::default().with_redoc().with_assets(assets),
+    );
+    let (_, csp, page) = call(&app, Method::GET, "/docs").await;
+    assert!(page.contains(
+        r#"<script src="https://cdn.example.com/redoc.js" integrity="sha384-abc" crossorigin="anonymous"></script>"#
+    ));
+    assert!(
+        csp.unwrap()
+            .contains("script-src 'self' https://cdn.example.com/redoc.js;")
+    );
+}
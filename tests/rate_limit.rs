@@ -0,0 +1,126 @@
+mod common;
+
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use axum::{
+    Router,
+    body::Body,
+    http::{HeaderName, Request, StatusCode, header::RETRY_AFTER},
+    routing::get,
+};
+use web_core::{
+    error::{Error, ErrorFormat},
+    middleware::rate_limit::{
+        MemoryRateLimitStore, Quota, RateLimitDecision, RateLimitKey, RateLimitLayer,
+        RateLimitOptions, RateLimitStore,
+    },
+    service_unavailable,
+    web_core::WebCore,
+};
+
+#[tokio::test]
+async fn zero_limit_is_denied() {
+    let store = MemoryRateLimitStore::new();
+    let decision = store
+        .hit("ip:127.0.0.1", &Quota::new(0, Duration::from_secs(60)))
+        .await
+        .unwrap();
+    assert!(!decision.allowed);
+    assert_eq!(decision.reset_after, Duration::from_secs(60));
+}
+
+#[tokio::test]
+async fn quota_is_counted_per_key() {
+    let store = MemoryRateLimitStore::new();
+    let quota = Quota::per_minute(1);
+    assert!(store.hit("a", &quota).await.unwrap().allowed);
+    assert!(!store.hit("a", &quota).await.unwrap().allowed);
+    assert!(store.hit("b", &quota).await.unwrap().allowed);
+}
+
+///Remembers the keys it is hit with.
+#[derive(Clone, Default)]
+struct RecordingStore {
+    keys: Arc<Mutex<Vec<String>>>,
+}
+
+#[async_trait]
+impl RateLimitStore for RecordingStore {
+    async fn hit(&self, key: &str, quota: &Quota) -> Result<RateLimitDecision, Error> {
+        self.keys.lock().unwrap().push(key.to_string());
+        Ok(RateLimitDecision {
+            allowed: true,
+            limit: quota.limit,
+            remaining: quota.limit,
+            reset_after: quota.window,
+        })
+    }
+}
+
+#[tokio::test]
+async fn api_key_is_hashed_before_reaching_the_store() {
+    let store = RecordingStore::default();
+    let app: Router = Router::new()
+        .route("/", get(|| async { "ok" }))
+        .with_rate_limit_layer(
+            RateLimitOptions::new(store.clone(), Quota::per_minute(10))
+                .with_key(RateLimitKey::ApiKey(HeaderName::from_static("x-api-key"))),
+        )
+        .with_state(common::state());
+
+    let request = Request::get("/")
+        .header("x-api-key", "plaintext-secret")
+        .body(Body::empty())
+        .unwrap();
+    common::send(&app, request).await;
+
+    let keys = store.keys.lock().unwrap();
+    assert_eq!(
+        keys.as_slice(),
+        ["default:key:600efdbf184d5b284575816b955e4303dcfe1000cbf3d2e9a5fd2b80910770c9"]
+    );
+}
+
+#[tokio::test]
+async fn retry_after_is_sent() {
+    for format in [ErrorFormat::Json, ErrorFormat::ProblemJson] {
+        let app: Router = Router::new()
+            .route("/", get(|| async { "ok" }))
+            .route(
+                "/maintenance",
+                get(|| async {
+                    Err::<(), Error>(service_unavailable!(
+                        retry_after = Duration::from_secs(120),
+                        "Down for maintenance."
+                    ))
+                }),
+            )
+            .with_rate_limit_layer(
+                RateLimitOptions::new(
+                    MemoryRateLimitStore::new(),
+                    Quota::new(1, Duration::from_secs(30)),
+                )
+                .with_key(RateLimitKey::ApiKey(HeaderName::from_static("x-api-key"))),
+            )
+            .with_web_core(common::web_core_options().with_error_format(format));
+
+        let request = || {
+            Request::get("/")
+                .header("x-api-key", "key")
+                .body(Body::empty())
+                .unwrap()
+        };
+        assert_eq!(common::send(&app, request()).await.status(), StatusCode::OK);
+        let response = common::send(&app, request()).await;
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()[RETRY_AFTER], "30");
+
+        let response = common::get(&app, "/maintenance").await;
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(response.headers()[RETRY_AFTER], "120");
+    }
+}
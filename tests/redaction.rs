@@ -0,0 +1,102 @@
+use axum::http::{HeaderMap, HeaderName, HeaderValue, header};
+use serde_json::{Value, json};
+use web_core::middleware::redaction::Redaction;
+
+#[test]
+fn sensitive_headers_are_redacted() {
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        header::AUTHORIZATION,
+        HeaderValue::from_static("Bearer abc"),
+    );
+    headers.insert(header::COOKIE, HeaderValue::from_static("session=abc"));
+    headers.insert("x-tenant", HeaderValue::from_static("acme"));
+    headers.insert("x-signature", HeaderValue::from_static("abc"));
+
+    let logged = Redaction::default()
+        .with_header(HeaderName::from_static("x-signature"))
+        .headers(&headers);
+
+    let logged: Value = serde_json::from_str(&logged).unwrap();
+    assert_eq!(
+        logged,
+        json!({
+            "authorization": "[REDACTED]",
+            "cookie": "[REDACTED]",
+            "x-tenant": "acme",
+            "x-signature": "[REDACTED]",
+        })
+    );
+}
+
+#[test]
+fn json_fields_are_redacted_at_any_depth() {
+    let body = json!({
+        "email": "a@example.com",
+        "password": "hunter2",
+        "session": {"refreshToken": "abc", "devices": [{"name": "phone", "Api_Key": "def"}]},
+    });
+
+    let logged = Redaction::default().body(
+        Some("application/json; charset=utf-8"),
+        body.to_string().as_bytes(),
+        4096,
+    );
+
+    let logged: Value = serde_json::from_str(&logged).unwrap();
+    assert_eq!(
+        logged,
+        json!({
+            "email": "a@example.com",
+            "password": "[REDACTED]",
+            "session": {
+                "refreshToken": "[REDACTED]",
+                "devices": [{"name": "phone", "Api_Key": "[REDACTED]"}],
+            },
+        })
+    );
+}
+
+#[test]
+fn form_fields_are_redacted() {
+    let logged = Redaction::default().with_field("pin").body(
+        Some("application/x-www-form-urlencoded"),
+        b"user=bob&password=hunter2&pass%77ord=hunter2&PIN=1234&otp",
+        4096,
+    );
+
+    assert_eq!(
+        logged,
+        "user=bob&password=[REDACTED]&pass%77ord=[REDACTED]&PIN=[REDACTED]&otp"
+    );
+}
+
+#[test]
+fn unparsed_bodies_are_only_described() {
+    let redaction = Redaction::default();
+
+    let malformed = redaction.body(Some("application/json"), br#"{"password": "hunt"#, 4096);
+    let xml = redaction.body(
+        Some("text/xml"),
+        b"<login><password>hunter2</password></login>",
+        4096,
+    );
+    let unknown = redaction.body(None, b"password=hunter2", 4096);
+
+    assert_eq!(malformed, "<18 bytes, unparsed application/json>");
+    assert_eq!(xml, "<43 bytes, unparsed text/xml>");
+    assert_eq!(unknown, "<16 bytes, unparsed>");
+}
+
+#[test]
+fn bodies_are_truncated_after_redaction() {
+    let body = json!({"token": "a-very-long-secret-value", "note": "é".repeat(10)});
+
+    let logged =
+        Redaction::default().body(Some("application/json"), body.to_string().as_bytes(), 24);
+
+    //Cut on a char boundary, and the secret never shows up.
+    assert!(logged.starts_with(r#"{"note":"ééééééé"#), "{logged}");
+    assert!(logged.ends_with("...<29 more bytes>"), "{logged}");
+    assert!(!logged.contains("secret"));
+}
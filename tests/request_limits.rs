@@ -0,0 +1,122 @@
+mod common;
+
+use std::time::Duration;
+
+use axum::{
+    Router,
+    body::Body,
+    http::{Request, StatusCode, header::RETRY_AFTER},
+    routing::{get, post},
+};
+use serde_json::Value;
+use web_core::{
+    extract::Json,
+    middleware::request_limits::{RequestLimitsLayer, RequestLimitsOptions},
+    web_core::WebCore,
+};
+
+async fn slow() -> &'static str {
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    "done"
+}
+
+fn post_json(path: &str, bytes: usize) -> Request<Body> {
+    let body = serde_json::to_vec(&"a".repeat(bytes)).unwrap();
+    Request::post(path)
+        .header("content-type", "application/json")
+        .body(Body::from(body))
+        .unwrap()
+}
+
+#[tokio::test]
+async fn slow_requests_time_out_with_408() {
+    let limits = RequestLimitsOptions::default().with_timeout(Some(Duration::from_millis(50)));
+    let app: Router = Router::new()
+        .route("/slow", get(slow))
+        .with_web_core(common::web_core_options().with_request_limits(limits));
+
+    let response = common::get(&app, "/slow").await;
+
+    assert_eq!(response.status(), StatusCode::REQUEST_TIMEOUT);
+    assert_eq!(common::json(response).await["code"], "request.timeout");
+}
+
+#[tokio::test]
+async fn large_bodies_are_rejected_with_413() {
+    let limits = RequestLimitsOptions::default().with_max_body_bytes(Some(64));
+    let app: Router = Router::new()
+        .route(
+            "/echo",
+            post(|Json(body): Json<Value>| async move { Json(body) }),
+        )
+        .with_web_core(common::web_core_options().with_request_limits(limits));
+
+    let small = common::send(&app, post_json("/echo", 10)).await;
+    let large = common::send(&app, post_json("/echo", 100)).await;
+
+    assert_eq!(small.status(), StatusCode::OK);
+    assert_eq!(large.status(), StatusCode::PAYLOAD_TOO_LARGE);
+}
+
+#[tokio::test]
+async fn requests_above_the_in_flight_limit_are_shed_with_503() {
+    let limits = RequestLimitsOptions::default().with_max_in_flight(1);
+    let app: Router = Router::new()
+        .route("/slow", get(slow))
+        .with_web_core(common::web_core_options().with_request_limits(limits));
+
+    let (first, second) = tokio::join!(common::get(&app, "/slow"), async {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        common::get(&app, "/slow").await
+    });
+
+    assert_eq!(first.status(), StatusCode::OK);
+    assert_eq!(second.status(), StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(second.headers()[RETRY_AFTER], "1");
+    assert_eq!(common::json(second).await["code"], "service.overloaded");
+}
+
+#[tokio::test]
+async fn route_groups_replace_the_global_limits() {
+    let global = RequestLimitsOptions::default()
+        .with_timeout(Some(Duration::from_millis(50)))
+        .with_max_body_bytes(Some(64));
+    let uploads = Router::new()
+        .route("/uploads/slow", get(slow))
+        .route(
+            "/uploads",
+            post(|Json(body): Json<Value>| async move { Json(body) }),
+        )
+        .with_request_limits_layer(
+            RequestLimitsOptions::default()
+                .with_timeout(Some(Duration::from_secs(5)))
+                .with_max_body_bytes(Some(1024)),
+        );
+    let app: Router = Router::new()
+        .route("/slow", get(slow))
+        .route(
+            "/echo",
+            post(|Json(body): Json<Value>| async move { Json(body) }),
+        )
+        .merge(uploads)
+        .with_web_core(common::web_core_options().with_request_limits(global));
+
+    assert_eq!(
+        common::get(&app, "/slow").await.status(),
+        StatusCode::REQUEST_TIMEOUT
+    );
+    assert_eq!(
+        common::get(&app, "/uploads/slow").await.status(),
+        StatusCode::OK
+    );
+    assert_eq!(
+        common::send(&app, post_json("/echo", 100)).await.status(),
+        StatusCode::PAYLOAD_TOO_LARGE
+    );
+    assert_eq!(
+        common::send(&app, post_json("/uploads", 100))
+            .await
+            .status(),
+        StatusCode::OK
+    );
+}
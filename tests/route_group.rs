@@ -0,0 +1,55 @@
+mod common;
+
+use axum::{Router, http::StatusCode, routing::get};
+use web_core::{
+    middleware::rate_limit::{MemoryRateLimitStore, Quota, RateLimitKey, RateLimitOptions},
+    route_group::{RouteGroup, RouteGroups},
+    web_core::WebCore,
+};
+
+fn app(groups: RouteGroups<()>) -> Router {
+    Router::new().with_web_core(common::web_core_options().with_route_groups(groups))
+}
+
+async fn status(app: &Router, path: &str) -> StatusCode {
+    common::get(app, path).await.status()
+}
+
+#[tokio::test]
+async fn unknown_path_is_not_found_after_admin_group() {
+    let app = app(RouteGroups::new()
+        .with_group(RouteGroup::public().route("/posts", get(|| async { "posts" })))
+        .with_group(RouteGroup::admin().route("/admin/users", get(|| async { "users" }))));
+
+    assert_eq!(status(&app, "/posts").await, StatusCode::OK);
+    assert_eq!(status(&app, "/admin/users").await, StatusCode::UNAUTHORIZED);
+    assert_eq!(status(&app, "/does-not-exist").await, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn unknown_path_is_not_found_after_internal_group() {
+    let app = app(RouteGroups::new()
+        .with_group(RouteGroup::public().route("/posts", get(|| async { "posts" })))
+        .with_group(
+            RouteGroup::internal("x-internal-key", "key").route("/jobs", get(|| async { "jobs" })),
+        ));
+
+    assert_eq!(status(&app, "/jobs").await, StatusCode::FORBIDDEN);
+    assert_eq!(status(&app, "/does-not-exist").await, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn unknown_paths_do_not_use_the_group_quota() {
+    let rate_limit = RateLimitOptions::new(MemoryRateLimitStore::new(), Quota::per_minute(1))
+        .with_key(RateLimitKey::custom(|_| Some(String::from("client"))));
+    let app = app(RouteGroups::new().with_group(
+        RouteGroup::public()
+            .route("/posts", get(|| async { "posts" }))
+            .with_rate_limit(rate_limit),
+    ));
+
+    assert_eq!(status(&app, "/does-not-exist").await, StatusCode::NOT_FOUND);
+    assert_eq!(status(&app, "/does-not-exist").await, StatusCode::NOT_FOUND);
+    assert_eq!(status(&app, "/posts").await, StatusCode::OK);
+    assert_eq!(status(&app, "/posts").await, StatusCode::TOO_MANY_REQUESTS);
+}
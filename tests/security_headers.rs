@@ -0,0 +1,46 @@
+mod common;
+
+use axum::{Router, http::header::CONTENT_SECURITY_POLICY, routing::get};
+use web_core::{
+    middleware::security_headers::{CspNonce, SecurityHeadersLayer, SecurityHeadersOptions},
+    web_core::WebCore,
+};
+
+const NONCE_POLICY: &str = "script-src 'self' 'nonce-{nonce}'";
+
+async fn csp_and_body(app: &Router, path: &str) -> (String, String) {
+    let response = common::get(app, path).await;
+    let csp = response.headers()[CONTENT_SECURITY_POLICY]
+        .to_str()
+        .unwrap()
+        .to_string();
+    (csp, common::text(response).await)
+}
+
+#[tokio::test]
+async fn overrides_get_a_nonce_when_the_global_policy_has_none() {
+    let overridden = Router::new()
+        .route("/page", get(|nonce: CspNonce| async move { nonce.0 }))
+        .with_security_headers_override(
+            SecurityHeadersOptions::default().with_content_security_policy(Some(NONCE_POLICY)),
+        );
+    let app: Router = Router::new()
+        .merge(overridden)
+        .route(
+            "/response",
+            get(|| async {
+                let options = SecurityHeadersOptions::default()
+                    .with_content_security_policy(Some(NONCE_POLICY));
+                (options, "ok")
+            }),
+        )
+        .with_web_core(common::web_core_options());
+
+    let (csp, nonce) = csp_and_body(&app, "/page").await;
+    assert!(!nonce.is_empty());
+    assert_eq!(csp, format!("script-src 'self' 'nonce-{nonce}'"));
+
+    let (csp, _) = csp_and_body(&app, "/response").await;
+    assert!(!csp.contains("{nonce}"));
+    assert!(csp.starts_with("script-src 'self' 'nonce-"));
+}
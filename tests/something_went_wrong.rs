@@ -0,0 +1,32 @@
+mod common;
+
+use axum::{
+    Router,
+    body::Body,
+    http::{Request, StatusCode},
+    routing::get,
+};
+use web_core::{error::Error, something_went_wrong, web_core::WebCore};
+
+#[tokio::test]
+async fn error_id_is_not_the_client_request_id() {
+    let app: Router = Router::new()
+        .route(
+            "/fail",
+            get(|| async { Err::<(), Error>(something_went_wrong!("failed")) }),
+        )
+        .with_web_core(common::web_core_options());
+
+    let request = Request::get("/fail")
+        .header("x-request-id", "client-chosen-id")
+        .body(Body::empty())
+        .unwrap();
+    let response = common::send(&app, request).await;
+    assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+
+    let body = common::json(response).await;
+    let error_id = body["error_id"].as_str().unwrap();
+    assert!(error_id.starts_with("Error-"));
+    assert!(!error_id.contains("client-chosen-id"));
+    assert_eq!(body["request_id"], "client-chosen-id");
+}